@@ -39,17 +39,45 @@ struct CliArgs {
     #[clap(short, long)]
     record_to_wav: Option<String>,
 
-    /// Model to use for transcription (e.g., "base.en", "tiny.en", "small.en").
+    /// Path to save only the speech (silence removed) as a WAV file.
+    #[clap(long)]
+    record_speech_to_wav: Option<String>,
+
+    /// Model to use for transcription (e.g., "base.en", "tiny.en", "small.en", "medium.en", "large-v3").
     #[clap(long)]
     model: Option<String>,
 
+    /// Path to a local ggml model file; overrides --model and needs no network access.
+    #[clap(long)]
+    model_path: Option<String>,
+
+    /// Save the model given with --model as the default for future runs.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    save_default_model: bool,
+
+    /// Smaller model used only for partial transcripts (e.g., "tiny.en"); the main model finalizes segments.
+    #[clap(long)]
+    partial_model: Option<String>,
+
     /// Disable computation and sending of partial (intermediate) transcripts.
     #[clap(long, action = clap::ArgAction::SetFalse)]
     compute_partials: bool,
 
+    /// Only re-decode partial transcripts once every N milliseconds of new audio.
+    #[clap(long)]
+    emit_partial_every_ms: Option<u32>,
+
+    /// Discard the first N milliseconds of microphone audio while the device warms up.
+    #[clap(long)]
+    discard_initial_ms: Option<u32>,
+
     /// List available audio input devices and exit.
     #[clap(long, action = clap::ArgAction::SetTrue)]
     list_devices: bool,
+
+    /// List models already downloaded to the cache and exit.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    list_cached: bool,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -78,6 +106,19 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
+    if args.list_cached {
+        println!("Cached models:");
+        let cached = whisper_stream_rs::list_cached_models()?;
+        if cached.is_empty() {
+            println!("  No models cached yet.");
+        }
+        for entry in cached {
+            let coreml = if entry.coreml_encoder { " (CoreML encoder present)" } else { "" };
+            println!("  {}: {}{}", entry.model, entry.path.display(), coreml);
+        }
+        return Ok(());
+    }
+
     let audio_device_name = args.audio_device_name.clone();
     let language = args.language.clone();
     let record_to_wav = args.record_to_wav.clone();
@@ -100,6 +141,26 @@ fn main() -> anyhow::Result<()> {
     } else {
         None
     };
+    if args.save_default_model {
+        match model {
+            Some(m) => whisper_stream_rs::set_default_model(m)?,
+            None => anyhow::bail!("--save-default-model requires --model"),
+        }
+    }
+    let partial_model = if let Some(model_str) = &args.partial_model {
+        match Model::from_str(model_str) {
+            Ok(m) => Some(m),
+            Err(_) => {
+                eprintln!(
+                    "[Error] Unknown partial model: '{}'. Available: {}",
+                    model_str, available_models_str
+                );
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
     let mut builder = WhisperStream::builder();
 
     if let Some(lang) = args.language {
@@ -130,15 +191,36 @@ fn main() -> anyhow::Result<()> {
     if let Some(record_path) = args.record_to_wav {
         builder = builder.record_to_wav(&record_path);
     }
+    if let Some(record_path) = args.record_speech_to_wav {
+        builder = builder.record_speech_to_wav(&record_path);
+    }
     if let Some(m) = model {
         builder = builder.model(m);
     }
+    if let Some(path) = &args.model_path {
+        builder = builder.model_path(path);
+    }
+    if let Some(m) = partial_model {
+        builder = builder.partial_model(m);
+    }
     builder = builder.compute_partials(args.compute_partials);
+    if let Some(interval) = args.emit_partial_every_ms {
+        builder = builder.emit_partial_every_ms(interval);
+    }
+    if let Some(discard) = args.discard_initial_ms {
+        builder = builder.discard_initial_ms(discard);
+    }
 
-    let selected_model = model.unwrap_or(Model::BaseEn);
+    let selected_model = model.unwrap_or_else(whisper_stream_rs::default_model);
     println!("--- Transcription Configuration ---");
     println!("Audio Device:     {}", audio_device_name.as_deref().unwrap_or("Default System Device"));
-    println!("Model:            {}", selected_model);
+    match &args.model_path {
+        Some(path) => println!("Model:            {}", path),
+        None => println!("Model:            {}", selected_model),
+    }
+    if let Some(m) = partial_model {
+        println!("Partial Model:    {}", m);
+    }
     println!("Step Duration:    {}ms", args.step_ms.unwrap_or(800));
     println!("Window Length:    {}ms", args.length_ms.unwrap_or(5000));
     println!("Keep Context:     {}ms", args.keep_ms.unwrap_or(200));
@@ -149,7 +231,16 @@ fn main() -> anyhow::Result<()> {
     println!("Record to WAV:    {}", record_to_wav.as_deref().unwrap_or("Disabled"));
     println!("---------------------------------");
 
-    let (_stream, rx) = builder.build()?;
+    let (stream, rx) = builder.build()?;
+
+    // First Ctrl+C drains the buffered audio so the last sentence isn't lost; a second one exits.
+    let signal_stream = stream.clone();
+    ctrlc::set_handler(move || {
+        if signal_stream.is_shutting_down() {
+            std::process::exit(130);
+        }
+        signal_stream.shutdown();
+    })?;
 
     println!("\n[System] Start speaking... (Press Ctrl+C to stop)\n");
 
@@ -14,6 +14,7 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Sample, SampleFormat, StreamConfig, InputCallbackInfo, StreamError as CpalStreamError};
 use rubato::{FftFixedInOut, Resampler};
+use crate::audio_utils::BufferPool;
 use crate::error::WhisperStreamError;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -25,6 +26,18 @@ pub struct AudioInput {
     pub sample_rate: u32,
     pub channels: u16,
     step_duration_ms: u32,
+    discard_initial_ms: u32,
+    buffer_pool: BufferPool,
+}
+
+/// Idle chunk buffers kept for reuse; a few steps of slack between capture and consumer.
+const MAX_POOLED_BUFFERS: usize = 8;
+
+/// Lists names of available audio input devices.
+///
+/// Any of these names can be passed to [`AudioInput::new`] to capture from that device.
+pub fn list_input_devices() -> Result<Vec<String>, WhisperStreamError> {
+    AudioInput::available_input_devices()
 }
 
 impl AudioInput {
@@ -152,9 +165,27 @@ impl AudioInput {
             sample_rate,
             channels,
             step_duration_ms: step_ms,
+            discard_initial_ms: 0,
+            buffer_pool: BufferPool::new(MAX_POOLED_BUFFERS),
         })
     }
 
+    /// Discards the first `ms` of audio after the stream starts.
+    ///
+    /// Many microphones deliver ramping or garbage samples while they warm up; dropping them
+    /// keeps the ramp-up out of recordings and transcripts.
+    pub fn with_discard_initial_ms(mut self, ms: u32) -> Self {
+        self.discard_initial_ms = ms;
+        self
+    }
+
+    /// Pool the capture callback draws chunk buffers from.
+    ///
+    /// Give each received chunk back with [`BufferPool::give`] once it has been consumed.
+    pub(crate) fn buffer_pool(&self) -> BufferPool {
+        self.buffer_pool.clone()
+    }
+
     /// Internal helper for cpal input stream and audio processing.
     ///
     /// Sets up cpal stream, converts samples to `f32`, buffers,
@@ -164,6 +195,7 @@ impl AudioInput {
     /// # Type Parameters
     /// * `T`: cpal sample type (`f32`, `i16`, `u16`).
     /// * `F`: Closure to convert `T` to `f32`.
+    #[allow(clippy::too_many_arguments)]
     fn process_audio_stream_internal<T, F>(
         device: &cpal::Device,
         config: &StreamConfig,
@@ -174,6 +206,8 @@ impl AudioInput {
         err_fn_outer_tx: Sender<Result<Vec<f32>, WhisperStreamError>>,
         convert_sample: F,
         stop_processing_signal: Arc<AtomicBool>,
+        discard_samples: usize, // Native interleaved samples to drop after the stream starts.
+        buffer_pool: BufferPool,
     ) -> Result<cpal::Stream, WhisperStreamError>
     where
         T: cpal::SizedSample,
@@ -185,6 +219,10 @@ impl AudioInput {
         // Pre-allocate buffers to reduce allocations in the hot loop
         let mut interleaved_chunk_buffer: Vec<f32> = Vec::with_capacity(device_samples_per_step * audio_channels);
         let mut mono_chunk_buffer: Vec<f32> = Vec::with_capacity(device_samples_per_step);
+        let mut discard_remaining = discard_samples;
+        let mut resampled_buffer: Vec<Vec<f32>> = resampler_opt.as_ref()
+            .map(|r| r.output_buffer_allocate(true))
+            .unwrap_or_default();
 
         device.build_input_stream(
             config,
@@ -193,6 +231,17 @@ impl AudioInput {
                     return;
                 }
 
+                let data = if discard_remaining > 0 {
+                    let skip = discard_remaining.min(data.len());
+                    discard_remaining -= skip;
+                    if discard_remaining == 0 {
+                        debug!("[Audio] Finished discarding device warm-up audio.");
+                    }
+                    &data[skip..]
+                } else {
+                    data
+                };
+
                 if log::log_enabled!(log::Level::Debug) {
                     let mut min = f32::INFINITY;
                     let mut max = f32::NEG_INFINITY;
@@ -257,21 +306,26 @@ impl AudioInput {
                     }
 
                     let final_chunk_data_result = if let Some(resampler) = resampler_opt.as_mut() {
-                        match resampler.process(&[&mono_chunk_buffer], None) { // Pass as slice of slices
-                            Ok(mut output_frames) => {
-                                if output_frames.is_empty() {
+                        match resampler.process_into_buffer(&[&mono_chunk_buffer], &mut resampled_buffer, None) {
+                            Ok((_, frames_out)) => {
+                                if frames_out == 0 {
                                     // Can happen if input chunk is too small for resampler.
                                     debug!("[Audio] Resampler returned no frames. Input size: {}. Accumulating more data.", mono_chunk_buffer.len());
                                     continue;
                                 }
-                                Ok(output_frames.remove(0))
+                                let mut chunk = buffer_pool.take();
+                                chunk.extend_from_slice(&resampled_buffer[0][..frames_out]);
+                                Ok(chunk)
                             }
                             Err(e) => {
                                 Err(WhisperStreamError::AudioResampling(format!("Resample failed: {:?}", e)))
                             }
                         }
                     } else {
-                        Ok(mono_chunk_buffer.clone()) // Clone as mono_chunk_buffer is reused
+                        // Copy into a pooled buffer, as mono_chunk_buffer is reused.
+                        let mut chunk = buffer_pool.take();
+                        chunk.extend_from_slice(&mono_chunk_buffer);
+                        Ok(chunk)
                     };
 
                     match final_chunk_data_result {
@@ -317,6 +371,8 @@ impl AudioInput {
         let (tx, rx) = mpsc::channel();
         let device_name_clone = self.device_name.clone();
         let step_duration_ms_clone = self.step_duration_ms;
+        let discard_initial_ms = self.discard_initial_ms;
+        let buffer_pool = self.buffer_pool.clone();
 
         #[cfg(target_os = "macos")]
         {
@@ -383,6 +439,7 @@ impl AudioInput {
             let target_sample_rate = 16000;
             // device_samples_per_step is the number of *native* samples for one chunk before any processing.
             let device_samples_per_step = (native_sample_rate as f32 * (step_duration_ms_clone as f32 / 1000.0)) as usize;
+            let discard_samples = (native_sample_rate as f32 * (discard_initial_ms as f32 / 1000.0)) as usize * audio_channels;
 
                         debug!("[Audio] Device config: Format={:?}, Rate={}, Channels={}",
                 default_config.sample_format(), native_sample_rate, audio_channels);
@@ -423,17 +480,17 @@ impl AudioInput {
                 SampleFormat::F32 => {
                     info!("[Audio] Attempting to access audio device...");
                     Self::process_audio_stream_internal::<f32, _>(
-                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &f32| *s, stop_processing_signal.clone())
+                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &f32| *s, stop_processing_signal.clone(), discard_samples, buffer_pool.clone())
                 }
                 SampleFormat::I16 => {
                     info!("[Audio] Attempting to access audio device...");
                     Self::process_audio_stream_internal::<i16, _>(
-                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &i16| s.to_float_sample(), stop_processing_signal.clone())
+                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &i16| s.to_float_sample(), stop_processing_signal.clone(), discard_samples, buffer_pool.clone())
                 }
                 SampleFormat::U16 => {
                     info!("[Audio] Attempting to access audio device...");
                     Self::process_audio_stream_internal::<u16, _>(
-                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &u16| s.to_float_sample(), stop_processing_signal.clone())
+                        &device, &config, tx_for_data_cb, audio_channels, device_samples_per_step, resampler_opt, tx_for_err_fn, |s: &u16| s.to_float_sample(), stop_processing_signal.clone(), discard_samples, buffer_pool.clone())
                 }
                 other_format => {
                     let err_msg = format!("Unsupported sample format: {:?}", other_format);
@@ -475,4 +532,18 @@ impl AudioInput {
         rx
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_input_devices_does_not_panic() {
+        // CI machines usually have no audio hardware; listing may fail but must not panic.
+        match list_input_devices() {
+            Ok(devices) => assert!(devices.iter().all(|name| !name.is_empty())),
+            Err(e) => assert!(!e.to_string().is_empty()),
+        }
+    }
+}
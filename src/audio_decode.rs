@@ -0,0 +1,128 @@
+//! Decoding of compressed audio files (MP3, FLAC, Ogg Vorbis) for transcription.
+//!
+//! Enabled with the `compressed-audio` feature. Decoding is done by symphonia; the decoded
+//! audio goes through the same [`downmix_to_mono`] and [`resample_to_16k`] helpers as WAV input.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+use log::warn;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use crate::audio_utils::{downmix_to_mono, resample_to_16k};
+use crate::error::WhisperStreamError;
+
+/// Decodes an MP3, FLAC or Ogg Vorbis file into Whisper-ready audio.
+///
+/// Returns mono 16kHz samples in `[-1.0, 1.0]` together with the sample rate of the source
+/// file. The format is detected from the file contents, using the extension as a hint.
+/// Corrupt frames are skipped with a warning. Codecs or stream layouts symphonia can't handle
+/// fail with [`WhisperStreamError::UnsupportedAudioFormat`]; anything else that stops decoding
+/// fails with [`WhisperStreamError::Decode`].
+pub fn decode_audio_file<P: AsRef<Path>>(path: P) -> Result<(Vec<f32>, u32), WhisperStreamError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(WhisperStreamError::from)?;
+    let extension = path.extension().and_then(|e| e.to_str());
+    decode_audio_source(Box::new(file), extension, &path.display().to_string())
+}
+
+/// Like [`decode_audio_file`], but for audio already held in memory, e.g. a downloaded body.
+///
+/// `extension` is a format hint like `"mp3"`; `name` identifies the audio in error messages.
+pub(crate) fn decode_audio_bytes(bytes: Vec<u8>, extension: Option<&str>, name: &str) -> Result<(Vec<f32>, u32), WhisperStreamError> {
+    decode_audio_source(Box::new(Cursor::new(bytes)), extension, name)
+}
+
+fn decode_audio_source(source: Box<dyn MediaSource>, extension: Option<&str>, name: &str) -> Result<(Vec<f32>, u32), WhisperStreamError> {
+    let stream = MediaSourceStream::new(source, Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let decode_error = |e: SymphoniaError| match e {
+        SymphoniaError::Unsupported(what) => WhisperStreamError::UnsupportedAudioFormat {
+            details: format!("{}: {}", name, what),
+        },
+        e => WhisperStreamError::Decode(format!("{}: {}", name, e)),
+    };
+    // Data no format reader recognizes is most likely not audio at all, so report it as
+    // undecodable rather than as an unsupported format.
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| WhisperStreamError::Decode(format!("{}: {}", name, e)))?;
+    let mut format = probed.format;
+    let track = format.tracks().iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| WhisperStreamError::Decode(format!("{}: no audio track found", name)))?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(decode_error)?;
+
+    let mut mono = Vec::new();
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(decode_error(e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                warn!("Skipping corrupt audio frame in {}: {}", name, e);
+                continue;
+            }
+            Err(e) => return Err(decode_error(e)),
+        };
+        let spec = *decoded.spec();
+        sample_rate = Some(spec.rate);
+        if buffer.as_ref().is_none_or(|b| b.capacity() < decoded.capacity()) {
+            buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+        }
+        if let Some(buffer) = buffer.as_mut() {
+            buffer.copy_interleaved_ref(decoded);
+            mono.extend(downmix_to_mono(buffer.samples(), spec.channels.count() as u16));
+        }
+    }
+
+    let sample_rate = sample_rate
+        .ok_or_else(|| WhisperStreamError::Decode(format!("{}: unknown sample rate", name)))?;
+    let samples = resample_to_16k(&mono, sample_rate)?.into_owned();
+    Ok((samples, sample_rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_audio_file_mp3() {
+        // 20 MPEG-1 Layer III frames of silence at 44.1kHz, 1152 samples each.
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/silence-44k.mp3");
+        let (samples, sample_rate) = decode_audio_file(path).expect("Failed to decode MP3");
+        assert_eq!(sample_rate, 44100);
+        // Allow for frames the decoder trims as encoder delay or padding.
+        let expected = (20 * 1152 * 16000usize).div_ceil(44100);
+        assert!(samples.len() <= expected && samples.len() + 1000 >= expected, "got {} samples", samples.len());
+        assert!(samples.iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_decode_audio_file_rejects_garbage() {
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-garbage.mp3");
+        std::fs::write(&path, b"definitely not audio").unwrap();
+        assert!(matches!(decode_audio_file(&path), Err(WhisperStreamError::Decode(_))));
+        let _ = std::fs::remove_file(&path);
+    }
+}
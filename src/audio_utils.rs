@@ -1,9 +1,34 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::Range;
+#[cfg(feature = "microphone")]
+use std::sync::{Arc, Mutex};
 use hound::{WavWriter, WavSpec, SampleFormat};
 use crate::error::WhisperStreamError;
 use std::fs;
-use std::path::Path;
+use std::io::{BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use log::{warn, debug};
+use rubato::{FftFixedIn, Resampler};
+
+/// Whisper expects mono audio at this sample rate.
+pub(crate) const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Minimum number of 16kHz samples passed to Whisper per call (1050ms); shorter audio is padded.
+pub(crate) const MIN_WHISPER_SAMPLES: usize = 16800;
+
+/// Where [`pad_audio_with_mode`] places the padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PadMode {
+    /// Silence after the audio.
+    #[default]
+    End,
+    /// Silence before the audio.
+    Start,
+    /// Silence split evenly before and after the audio; an odd sample goes to the end.
+    Center,
+}
 
 /// Pads an audio segment with silence if it's shorter than `min_samples`.
 ///
@@ -15,74 +40,933 @@ use log::{warn, debug};
 /// A `Cow<[f32]>` which is either a borrowed slice of the original audio
 /// if no padding was needed, or an owned, padded `Vec<f32>`.
 pub fn pad_audio_if_needed(audio_segment: &[f32], min_samples: usize) -> Cow<'_, [f32]> {
-    if audio_segment.len() < min_samples {
-        let mut padded_segment = Vec::with_capacity(min_samples);
-        padded_segment.extend_from_slice(audio_segment);
-        let padding_needed = min_samples - audio_segment.len();
-        padded_segment.extend(std::iter::repeat_n(0.0f32, padding_needed));
-        Cow::Owned(padded_segment)
-    } else {
-        Cow::Borrowed(audio_segment)
+    pad_audio_with_value(audio_segment, min_samples, 0.0)
+}
+
+/// Like [`pad_audio_if_needed`], but pads with `value` instead of silence.
+pub fn pad_audio_with_value(audio_segment: &[f32], min_samples: usize, value: f32) -> Cow<'_, [f32]> {
+    pad_audio(audio_segment, min_samples, PadMode::End, value)
+}
+
+/// Like [`pad_audio_if_needed`], but pads by repeating the last sample.
+///
+/// Avoids the abrupt step to silence that shows up as a broadband edge in spectrograms.
+/// An empty segment is padded with silence.
+pub fn pad_audio_edge(audio_segment: &[f32], min_samples: usize) -> Cow<'_, [f32]> {
+    pad_audio_with_value(audio_segment, min_samples, audio_segment.last().copied().unwrap_or(0.0))
+}
+
+/// Like [`pad_audio_if_needed`], but places the silence according to `mode`.
+pub fn pad_audio_with_mode(audio_segment: &[f32], min_samples: usize, mode: PadMode) -> Cow<'_, [f32]> {
+    pad_audio(audio_segment, min_samples, mode, 0.0)
+}
+
+fn pad_audio(audio_segment: &[f32], min_samples: usize, mode: PadMode, value: f32) -> Cow<'_, [f32]> {
+    if audio_segment.len() >= min_samples {
+        return Cow::Borrowed(audio_segment);
+    }
+    let padding_needed = min_samples - audio_segment.len();
+    let padding_before = match mode {
+        PadMode::End => 0,
+        PadMode::Start => padding_needed,
+        PadMode::Center => padding_needed / 2,
+    };
+    let mut padded_segment = Vec::with_capacity(min_samples);
+    padded_segment.extend(std::iter::repeat_n(value, padding_before));
+    padded_segment.extend_from_slice(audio_segment);
+    padded_segment.extend(std::iter::repeat_n(value, padding_needed - padding_before));
+    Cow::Owned(padded_segment)
+}
+
+/// Like [`pad_audio_if_needed`], but pads into the reusable `scratch` buffer instead of
+/// allocating a new one on every call.
+#[cfg(feature = "microphone")]
+pub(crate) fn pad_audio_into<'a>(audio_segment: &'a [f32], min_samples: usize, scratch: &'a mut Vec<f32>) -> &'a [f32] {
+    if audio_segment.len() >= min_samples {
+        return audio_segment;
+    }
+    scratch.clear();
+    scratch.extend_from_slice(audio_segment);
+    scratch.resize(min_samples, 0.0);
+    scratch
+}
+
+/// A shared pool of sample buffers, so steady-state streaming doesn't allocate per chunk.
+///
+/// The capture callback takes a buffer for each chunk it sends and the consumer gives it back
+/// once the samples are copied out. At most `max_pooled` idle buffers are kept.
+#[cfg(feature = "microphone")]
+#[derive(Debug, Clone)]
+pub(crate) struct BufferPool {
+    buffers: Arc<Mutex<Vec<Vec<f32>>>>,
+    max_pooled: usize,
+}
+
+#[cfg(feature = "microphone")]
+impl BufferPool {
+    pub(crate) fn new(max_pooled: usize) -> Self {
+        Self { buffers: Arc::new(Mutex::new(Vec::with_capacity(max_pooled))), max_pooled }
+    }
+
+    /// Returns an empty buffer, reusing a pooled one if available.
+    ///
+    /// Never blocks: if the pool is locked (e.g. when called from the audio callback while the
+    /// consumer is returning a buffer), a new buffer is allocated instead.
+    pub(crate) fn take(&self) -> Vec<f32> {
+        let mut buffer = self.buffers.try_lock().ok().and_then(|mut buffers| buffers.pop()).unwrap_or_default();
+        buffer.clear();
+        buffer
+    }
+
+    /// Returns `buffer` to the pool for reuse.
+    pub(crate) fn give(&self, buffer: Vec<f32>) {
+        if let Ok(mut buffers) = self.buffers.lock()
+            && buffers.len() < self.max_pooled
+        {
+            buffers.push(buffer);
+        }
+    }
+}
+
+/// Reads a 16-bit integer PCM WAV file without converting its samples.
+///
+/// Keeps integer pipelines integer end-to-end: the samples are returned exactly as stored
+/// (interleaved if multi-channel) alongside the file's spec. Files in any other sample
+/// format are rejected with [`WhisperStreamError::UnsupportedAudioFormat`].
+pub fn read_wav_i16<P: AsRef<Path>>(path: P) -> Result<(Vec<i16>, WavSpec), WhisperStreamError> {
+    let mut reader = hound::WavReader::open(path).map_err(wav_read_error)?;
+    let spec = reader.spec();
+    if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(WhisperStreamError::UnsupportedAudioFormat {
+            details: format!("{}; expected 16-bit Int", describe_wav_spec(&spec)),
+        });
+    }
+    let samples = reader.samples::<i16>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| WhisperStreamError::Hound { source: e })?;
+    Ok((samples, spec))
+}
+
+/// Input frames the resampler in [`resample_to_16k`] processes at a time.
+const RESAMPLE_CHUNK_FRAMES: usize = 1024;
+
+/// Resamples mono audio at `input_rate` Hz to the 16kHz Whisper expects.
+///
+/// Uses rubato's FFT resampler, whose anti-aliasing filter removes content above 8kHz
+/// instead of folding it back into the speech band. The output is aligned with the input
+/// (the resampler's delay is removed) and has `ceil(len * 16000 / input_rate)` samples.
+///
+/// # Returns
+/// A `Cow<[f32]>` which borrows `samples` if they already are 16kHz, or owns the
+/// resampled audio otherwise.
+pub fn resample_to_16k(samples: &[f32], input_rate: u32) -> Result<Cow<'_, [f32]>, WhisperStreamError> {
+    if input_rate == WHISPER_SAMPLE_RATE || samples.is_empty() {
+        return Ok(Cow::Borrowed(samples));
+    }
+    let resampling_error = |e: &dyn std::fmt::Display| {
+        WhisperStreamError::AudioResampling(format!("Failed to resample {}Hz audio to {}Hz: {}", input_rate, WHISPER_SAMPLE_RATE, e))
+    };
+    let mut resampler = FftFixedIn::<f32>::new(input_rate as usize, WHISPER_SAMPLE_RATE as usize, RESAMPLE_CHUNK_FRAMES, 2, 1)
+        .map_err(|e| resampling_error(&e))?;
+
+    let delay = resampler.output_delay();
+    let expected_len = (samples.len() as u64 * WHISPER_SAMPLE_RATE as u64).div_ceil(input_rate as u64) as usize;
+    let mut output = Vec::with_capacity(delay + expected_len + resampler.output_frames_max());
+    let mut chunks = samples.chunks_exact(RESAMPLE_CHUNK_FRAMES);
+    for chunk in &mut chunks {
+        let resampled = resampler.process(&[chunk], None).map_err(|e| resampling_error(&e))?;
+        output.extend_from_slice(&resampled[0]);
+    }
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let resampled = resampler.process_partial(Some(&[remainder]), None).map_err(|e| resampling_error(&e))?;
+        output.extend_from_slice(&resampled[0]);
+    }
+    // Flush the samples still held back by the resampler's delay.
+    while output.len() < delay + expected_len {
+        let resampled = resampler.process_partial::<&[f32]>(None, None).map_err(|e| resampling_error(&e))?;
+        output.extend_from_slice(&resampled[0]);
+    }
+    output.drain(..delay);
+    output.truncate(expected_len);
+    Ok(Cow::Owned(output))
+}
+
+/// Mono audio decoded from a WAV file by [`read_wav_as_f32`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavAudio {
+    /// Mono samples in `[-1.0, 1.0]`.
+    pub samples: Vec<f32>,
+    /// Sample rate of the file in Hz. Whisper needs 16000; other rates must be resampled.
+    pub sample_rate: u32,
+}
+
+/// Reads a WAV file as mono f32 samples in `[-1.0, 1.0]`, whatever its bit depth or sample format.
+///
+/// Multi-channel files are downmixed with [`downmix_to_mono`]. The audio is returned at the
+/// file's own sample rate, which is reported alongside the samples.
+pub fn read_wav_as_f32<P: AsRef<Path>>(path: P) -> Result<WavAudio, WhisperStreamError> {
+    let reader = hound::WavReader::open(path).map_err(wav_read_error)?;
+    let (interleaved, spec) = decode_wav_samples(reader)?;
+    let samples = if spec.channels == 1 { interleaved } else { downmix_to_mono(&interleaved, spec.channels) };
+    Ok(WavAudio { samples, sample_rate: spec.sample_rate })
+}
+
+/// Averages interleaved `channels`-channel audio into a single mono stream.
+///
+/// Mono input is returned unchanged. A trailing partial frame is dropped.
+pub fn downmix_to_mono(samples: &[f32], channels: u16) -> Vec<f32> {
+    let channels = channels.max(1) as usize;
+    if channels == 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
+
+/// Decodes all samples of `reader` to interleaved f32 in `[-1.0, 1.0]`, returning them with the spec.
+///
+/// Fails with [`WhisperStreamError::UnsupportedAudioFormat`] for layouts that can't be
+/// converted: no channels, float samples other than 32-bit, or integers wider than 32 bits.
+pub(crate) fn decode_wav_samples<R: std::io::Read>(mut reader: hound::WavReader<R>) -> Result<(Vec<f32>, WavSpec), WhisperStreamError> {
+    let spec = reader.spec();
+    let supported = spec.channels > 0 && match spec.sample_format {
+        SampleFormat::Float => spec.bits_per_sample == 32,
+        SampleFormat::Int => (1..=32).contains(&spec.bits_per_sample),
+    };
+    if !supported {
+        return Err(WhisperStreamError::UnsupportedAudioFormat { details: describe_wav_spec(&spec) });
+    }
+    let samples = match spec.sample_format {
+        SampleFormat::Float => reader.samples::<f32>()
+            .map(|s| s.map(|v| v.clamp(-1.0, 1.0)))
+            .collect::<Result<_, _>>(),
+        SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()
+        }
+    }.map_err(wav_read_error)?;
+    Ok((samples, spec))
+}
+
+/// Maps hound's "unsupported" error to [`WhisperStreamError::UnsupportedAudioFormat`], keeping
+/// other failures as [`WhisperStreamError::Hound`].
+pub(crate) fn wav_read_error(e: hound::Error) -> WhisperStreamError {
+    match e {
+        hound::Error::Unsupported => WhisperStreamError::UnsupportedAudioFormat {
+            details: "WAV encoding not supported by the reader".to_string(),
+        },
+        e => WhisperStreamError::Hound { source: e },
+    }
+}
+
+fn describe_wav_spec(spec: &WavSpec) -> String {
+    format!("{}-bit {:?} WAV with {} channel(s) at {}Hz", spec.bits_per_sample, spec.sample_format, spec.channels, spec.sample_rate)
+}
+
+/// Returns the part of `samples` between the first and last sample louder than `threshold`.
+///
+/// `margin` samples are kept on either side of the loud region (as far as the input allows)
+/// so the onset and decay of speech aren't clipped. Input without any sample above
+/// `threshold` yields an empty slice.
+pub fn trim_silence(samples: &[f32], threshold: f32, margin: usize) -> &[f32] {
+    let is_loud = |s: &f32| s.abs() > threshold;
+    match (samples.iter().position(is_loud), samples.iter().rposition(is_loud)) {
+        (Some(first), Some(last)) => {
+            let start = first.saturating_sub(margin);
+            let end = last.saturating_add(margin).saturating_add(1).min(samples.len());
+            &samples[start..end]
+        }
+        _ => &samples[..0],
+    }
+}
+
+/// Merges speech ranges separated by short pauses into single ranges.
+///
+/// Ranges that overlap, touch, or are separated by a gap of at most `max_gap_samples` are
+/// combined, so a brief breath mid-sentence doesn't split an utterance. The input doesn't
+/// need to be sorted; the output is sorted by start and non-overlapping.
+pub fn coalesce_segments(ranges: &[Range<usize>], max_gap_samples: usize) -> Vec<Range<usize>> {
+    let mut sorted: Vec<Range<usize>> = ranges.iter().filter(|r| !r.is_empty()).cloned().collect();
+    sorted.sort_by_key(|r| r.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(max_gap_samples) => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Joins `segments` into one buffer with `gap_samples` of silence between consecutive ones.
+///
+/// Use it to glue speech cut out with [`coalesce_segments`] or [`trim_silence`] back together,
+/// e.g. to archive VAD-split segments as one file. No gap is added before the first or after the
+/// last segment, so a single segment is returned unchanged and no segments give an empty buffer.
+pub fn concat_segments(segments: &[&[f32]], gap_samples: usize) -> Vec<f32> {
+    let total = segments.iter().map(|s| s.len()).sum::<usize>() + gap_samples * segments.len().saturating_sub(1);
+    let mut joined = Vec::with_capacity(total);
+    for (i, segment) in segments.iter().enumerate() {
+        if i > 0 {
+            joined.resize(joined.len() + gap_samples, 0.0);
+        }
+        joined.extend_from_slice(segment);
+    }
+    joined
+}
+
+/// Measures the integrated loudness of mono `samples` in LUFS, per ITU-R BS.1770-4.
+///
+/// The signal is K-weighted, split into 400ms blocks with 75% overlap, and gated at
+/// -70 LUFS (absolute) and 10 LU below the ungated loudness (relative). Returns
+/// `f32::NEG_INFINITY` when nothing passes the gates, e.g. for silence.
+///
+/// Use the result to compute a gain towards a target such as -16 LUFS for podcasts:
+/// `10f32.powf((target - measured) / 20.0)`.
+pub fn measure_lufs(samples: &[f32], sample_rate: u32) -> f32 {
+    if samples.is_empty() || sample_rate == 0 {
+        return f32::NEG_INFINITY;
+    }
+    let fs = sample_rate as f64;
+
+    // Bilinear-transform designs of the BS.1770 filters, valid at any sample rate
+    // (coefficients as derived for libebur128).
+    // Stage 1: high-shelf modelling the acoustic effect of the head.
+    let shelf = {
+        let k = (std::f64::consts::PI * 1_681.974_450_955_533 / fs).tan();
+        let q = 0.707_175_236_955_419_6;
+        let vh = 10f64.powf(3.999_843_853_973_347 / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        Biquad::new(
+            [vh + vb * k / q + k * k, 2.0 * (k * k - vh), vh - vb * k / q + k * k],
+            [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+        )
+    };
+    // Stage 2: RLB high-pass.
+    let high_pass = {
+        let k = (std::f64::consts::PI * 38.135_470_876_024_44 / fs).tan();
+        let q = 0.500_327_037_323_877_3;
+        let a0 = 1.0 + k / q + k * k;
+        // The numerator is left unnormalized, matching the reference coefficients.
+        Biquad::new(
+            [a0, -2.0 * a0, a0],
+            [a0, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+        )
+    };
+
+    let mut stages = [shelf, high_pass];
+    let squared: Vec<f64> = samples.iter()
+        .map(|&s| {
+            let weighted = stages.iter_mut().fold(s as f64, |x, stage| stage.process(x));
+            weighted * weighted
+        })
+        .collect();
+
+    let block_len = ((0.4 * fs) as usize).clamp(1, squared.len());
+    let hop = (block_len / 4).max(1);
+    let block_powers: Vec<f64> = (0..=(squared.len() - block_len) / hop)
+        .map(|i| squared[i * hop..i * hop + block_len].iter().sum::<f64>() / block_len as f64)
+        .collect();
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let gated_mean = |threshold: f64| {
+        let passed: Vec<f64> = block_powers.iter().copied().filter(|&p| p > 0.0 && loudness(p) > threshold).collect();
+        (!passed.is_empty()).then(|| passed.iter().sum::<f64>() / passed.len() as f64)
+    };
+
+    let Some(absolute_gated) = gated_mean(-70.0) else {
+        return f32::NEG_INFINITY;
+    };
+    match gated_mean(loudness(absolute_gated) - 10.0) {
+        Some(power) => loudness(power) as f32,
+        None => f32::NEG_INFINITY,
+    }
+}
+
+/// Direct-form I biquad filter with normalized coefficients.
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        Self {
+            b: [b[0] / a[0], b[1] / a[0], b[2] / a[0]],
+            a: [a[1] / a[0], a[2] / a[0]],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1] - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// Scales `samples` so the loudest one has an absolute value of `target_peak` (use `1.0` for full scale).
+///
+/// Silent input (all zeros) is left untouched rather than divided by zero. Unlike [`Agc`],
+/// this applies one gain to the whole buffer, so it suits complete recordings.
+pub fn normalize_peak(samples: &mut [f32], target_peak: f32) {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    if peak > 0.0 {
+        let gain = target_peak / peak;
+        samples.iter_mut().for_each(|s| *s *= gain);
+    }
+}
+
+/// Linearly fades the first `fade_samples` of `samples` in and the last `fade_samples` out.
+///
+/// The outermost samples become zero, removing the click of a hard cut at segment
+/// boundaries. `fade_samples` is clamped to half the buffer so the ramps never overlap.
+pub fn apply_fade(samples: &mut [f32], fade_samples: usize) {
+    let fade_samples = fade_samples.min(samples.len() / 2);
+    let len = samples.len();
+    for i in 0..fade_samples {
+        let gain = i as f32 / fade_samples as f32;
+        samples[i] *= gain;
+        samples[len - 1 - i] *= gain;
+    }
+}
+
+/// Multiplies `samples` by `gain`, clamping the result to `[-1.0, 1.0]`.
+pub fn apply_gain(samples: &mut [f32], gain: f32) {
+    samples.iter_mut().for_each(|s| *s = (*s * gain).clamp(-1.0, 1.0));
+}
+
+/// Adaptive, stateful automatic gain control.
+///
+/// Each call to [`Agc::process`] measures the RMS of the chunk and moves the gain toward
+/// `target_rms / rms`, reacting quickly to loud input (attack) and slowly to quiet input
+/// (release). The gain never exceeds `max_gain`, so pure noise isn't amplified without bound.
+#[derive(Debug, Clone)]
+pub struct Agc {
+    target_rms: f32,
+    max_gain: f32,
+    attack: f32,
+    release: f32,
+    gain: f32,
+}
+
+impl Agc {
+    /// RMS below which a chunk is treated as silence and the gain is left unchanged.
+    const SILENCE_RMS: f32 = 1e-4;
+
+    /// Creates a new `Agc` starting at unity gain.
+    ///
+    /// # Arguments
+    /// * `target_rms`: The RMS level to steer towards (e.g. `0.1`).
+    /// * `max_gain`: Upper bound for the applied gain.
+    pub fn new(target_rms: f32, max_gain: f32) -> Self {
+        Self {
+            target_rms,
+            max_gain: max_gain.max(1.0),
+            attack: 0.5,
+            release: 0.05,
+            gain: 1.0,
+        }
+    }
+
+    /// Sets the attack and release smoothing coefficients, each clamped to `(0.0, 1.0]`.
+    ///
+    /// `attack` applies when the gain has to drop, `release` when it has to rise.
+    pub fn with_attack_release(mut self, attack: f32, release: f32) -> Self {
+        self.attack = attack.clamp(f32::EPSILON, 1.0);
+        self.release = release.clamp(f32::EPSILON, 1.0);
+        self
+    }
+
+    /// Returns the gain currently being applied.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Applies gain to `samples` in place and updates the gain for the next chunk.
+    ///
+    /// The gain is ramped linearly across the chunk to avoid audible steps. Output is clamped to `[-1.0, 1.0]`.
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let rms = rms(samples);
+        let start_gain = self.gain;
+        if rms > Self::SILENCE_RMS {
+            let desired = (self.target_rms / rms).min(self.max_gain);
+            let coeff = if desired < self.gain { self.attack } else { self.release };
+            self.gain += coeff * (desired - self.gain);
+        }
+
+        let step = (self.gain - start_gain) / samples.len() as f32;
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let gain = start_gain + step * (i + 1) as f32;
+            *sample = (*sample * gain).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Root-mean-square energy of `samples` (`0.0` for an empty slice).
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Returns `true` if the RMS energy of `frame` is above `energy_threshold`.
+///
+/// This is the per-frame decision [`Vad`] and [`SilenceDetector`] are built on; use it
+/// directly for a stateless check.
+pub fn is_speech(frame: &[f32], energy_threshold: f32) -> bool {
+    rms(frame) > energy_threshold
+}
+
+/// Detects the end of an utterance: fires once speech has been followed by enough silence.
+///
+/// Feed it consecutive frames of any size; silent frames accumulate until their total length
+/// reaches `min_silence_samples`, at which point [`SilenceDetector::process_frame`] returns
+/// `true` exactly once. Silence before any speech never fires, so a recorder using this to
+/// close segments doesn't emit empty ones.
+#[derive(Debug, Clone)]
+pub struct SilenceDetector {
+    energy_threshold: f32,
+    min_silence_samples: usize,
+    silent_samples: usize,
+    heard_speech: bool,
+}
+
+impl SilenceDetector {
+    /// Creates a new `SilenceDetector`.
+    ///
+    /// # Arguments
+    /// * `energy_threshold`: RMS above which a frame counts as speech.
+    /// * `min_silence_samples`: Length of silence that ends an utterance (e.g. 16000 for 1s at 16kHz).
+    pub fn new(energy_threshold: f32, min_silence_samples: usize) -> Self {
+        Self { energy_threshold, min_silence_samples, silent_samples: 0, heard_speech: false }
+    }
+
+    /// Feeds the next frame and returns `true` if it completes a long enough silence after speech.
+    pub fn process_frame(&mut self, frame: &[f32]) -> bool {
+        if is_speech(frame, self.energy_threshold) {
+            self.heard_speech = true;
+            self.silent_samples = 0;
+            return false;
+        }
+        self.silent_samples = self.silent_samples.saturating_add(frame.len());
+        if self.heard_speech && self.silent_samples >= self.min_silence_samples {
+            self.heard_speech = false;
+            return true;
+        }
+        false
+    }
+
+    /// Returns the number of consecutive silent samples seen so far.
+    pub fn silent_samples(&self) -> usize {
+        self.silent_samples
+    }
+
+    /// Clears all state, e.g. when starting an unrelated stream.
+    pub fn reset(&mut self) {
+        self.silent_samples = 0;
+        self.heard_speech = false;
+    }
+}
+
+/// Energy-based voice activity detector that works on fixed-size frames.
+///
+/// All state (the partially filled frame and the hangover counter) is carried across calls to
+/// [`Vad::process`], so a stream fed in arbitrary chunk sizes yields exactly the same
+/// decisions as the same audio fed in one buffer. Words spanning a chunk boundary are
+/// therefore not clipped.
+#[derive(Debug, Clone)]
+pub struct Vad {
+    frame_len: usize,
+    energy_threshold: f32,
+    hangover_frames: usize,
+    pending: Vec<f32>,
+    hangover_remaining: usize,
+    speaking: bool,
+}
+
+impl Vad {
+    /// Creates a new `Vad`.
+    ///
+    /// # Arguments
+    /// * `frame_len`: Number of samples per decision frame (e.g. 480 for 30ms at 16kHz).
+    /// * `energy_threshold`: RMS above which a frame counts as speech.
+    /// * `hangover_frames`: Number of quiet frames still reported as speech after speech ends.
+    pub fn new(frame_len: usize, energy_threshold: f32, hangover_frames: usize) -> Self {
+        let frame_len = frame_len.max(1);
+        Self {
+            frame_len,
+            energy_threshold,
+            hangover_frames,
+            pending: Vec::with_capacity(frame_len),
+            hangover_remaining: 0,
+            speaking: false,
+        }
+    }
+
+    /// Feeds `samples` and returns one speech/silence decision per frame completed by them.
+    ///
+    /// Samples that don't fill a whole frame are kept and completed by the next call.
+    pub fn process(&mut self, samples: &[f32]) -> Vec<bool> {
+        let mut decisions = Vec::with_capacity((self.pending.len() + samples.len()) / self.frame_len);
+        let mut remaining = samples;
+        while !remaining.is_empty() {
+            let take = (self.frame_len - self.pending.len()).min(remaining.len());
+            self.pending.extend_from_slice(&remaining[..take]);
+            remaining = &remaining[take..];
+            if self.pending.len() == self.frame_len {
+                decisions.push(self.decide_frame());
+                self.pending.clear();
+            }
+        }
+        decisions
+    }
+
+    /// Returns the decision for the most recently completed frame.
+    pub fn is_speaking(&self) -> bool {
+        self.speaking
+    }
+
+    /// Clears all carried-over state, e.g. when starting an unrelated stream.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.hangover_remaining = 0;
+        self.speaking = false;
+    }
+
+    fn decide_frame(&mut self) -> bool {
+        if is_speech(&self.pending, self.energy_threshold) {
+            self.hangover_remaining = self.hangover_frames;
+            self.speaking = true;
+        } else if self.hangover_remaining > 0 {
+            self.hangover_remaining -= 1;
+            self.speaking = true;
+        } else {
+            self.speaking = false;
+        }
+        self.speaking
     }
 }
 
-/// Handles recording audio chunks to a WAV file.
-pub struct WavAudioRecorder {
-    writer: Option<WavWriter<std::io::BufWriter<std::fs::File>>>,
+/// Handles recording audio chunks to a WAV file, or to any seekable sink given to
+/// [`WavAudioRecorder::from_writer`].
+pub struct WavAudioRecorder<W: Write + Seek = BufWriter<fs::File>> {
+    writer: Option<WavWriter<W>>,
     path: String,
     is_recording_active: bool,
+    channel_gains: Vec<f32>,
+    samples_written: u64,
+    clipped_samples: u64,
+    warned_interleaved: bool,
+    silence_gate: Option<SilenceGate>,
+    skipped_samples: u64,
+    in_skipped_silence: bool,
+}
+
+/// Drops silent chunks from a [`WavAudioRecorder`] to save disk on long unattended
+/// recordings; see [`WavAudioRecorder::set_silence_gate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceGate {
+    /// RMS energy at or below which a whole chunk counts as silence (see [`is_speech`]).
+    pub energy_threshold: f32,
+    /// Frames of digital silence written where silence was dropped, so the gaps between
+    /// retained segments stay audible. `0` splices the segments directly.
+    pub marker_frames: usize,
+}
+
+const MAX_CHANNEL_GAIN: f32 = 16.0;
+
+/// Share of a chunk's samples that may clip before [`WavAudioRecorder`] warns about it.
+const CLIPPING_WARN_RATIO: f64 = 0.001;
+
+/// Fails with [`WhisperStreamError::WavWrite`] unless [`WavAudioRecorder`] can write `spec`.
+fn check_recording_spec(spec: WavSpec) -> Result<(), WhisperStreamError> {
+    if !matches!((spec.sample_format, spec.bits_per_sample), (SampleFormat::Int, 16) | (SampleFormat::Float, 32)) {
+        return Err(WhisperStreamError::WavWrite(format!(
+            "Unsupported WAV sample format {:?} at {} bits; use 16-bit Int or 32-bit Float", spec.sample_format, spec.bits_per_sample
+        )));
+    }
+    if spec.channels == 0 || spec.sample_rate == 0 {
+        return Err(WhisperStreamError::WavWrite(format!(
+            "Invalid WAV spec: {} channels at {}Hz", spec.channels, spec.sample_rate
+        )));
+    }
+    Ok(())
 }
 
 impl WavAudioRecorder {
-    /// Creates a new `WavAudioRecorder`.
+    /// Largest per-channel gain accepted by [`WavAudioRecorder::set_channel_gains`].
+    pub const MAX_CHANNEL_GAIN: f32 = MAX_CHANNEL_GAIN;
+
+    /// Returns a builder starting from the defaults of [`WavAudioRecorder::new`]: recording
+    /// disabled, 16kHz mono 16-bit integer samples, no append.
+    pub fn builder() -> WavAudioRecorderBuilder {
+        WavAudioRecorderBuilder {
+            path: None,
+            sample_rate: WHISPER_SAMPLE_RATE,
+            channels: 1,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+            append: false,
+            silence_gate: None,
+        }
+    }
+
+    /// Creates a new `WavAudioRecorder` writing 16-bit integer samples.
     ///
     /// # Arguments
     /// * `path_opt`: Optional path to save the WAV file. If `None`, recording is disabled.
     pub fn new(path_opt: Option<&str>) -> Result<Self, WhisperStreamError> {
+        Self::new_with_format(path_opt, SampleFormat::Int, 16)
+    }
+
+    /// Creates a new `WavAudioRecorder` writing samples in the given format.
+    ///
+    /// `SampleFormat::Int` at 16 bits matches [`WavAudioRecorder::new`]. `SampleFormat::Float`
+    /// at 32 bits stores the f32 samples as-is, without quantization or clamping, for tools
+    /// that want full precision. Other combinations are rejected.
+    ///
+    /// # Arguments
+    /// * `path_opt`: Optional path to save the WAV file. If `None`, recording is disabled.
+    /// * `sample_format`: Whether to store integer or floating point samples.
+    /// * `bits_per_sample`: 16 for `SampleFormat::Int`, 32 for `SampleFormat::Float`.
+    pub fn new_with_format(path_opt: Option<&str>, sample_format: SampleFormat, bits_per_sample: u16) -> Result<Self, WhisperStreamError> {
+        let spec = WavSpec {
+            channels: 1,        // Whisper processes mono audio
+            sample_rate: 16000, // Whisper processes 16kHz audio
+            bits_per_sample,
+            sample_format,
+        };
+        Self::with_spec(path_opt, spec)
+    }
+
+    /// Creates a new `WavAudioRecorder` with an arbitrary sample rate and channel count.
+    ///
+    /// Use this to archive raw device audio (e.g. 48kHz stereo) before it is resampled for
+    /// Whisper. With more than one channel, chunks passed to `write_audio_chunk` are treated
+    /// as interleaved. The sample format rules of [`WavAudioRecorder::new_with_format`] apply.
+    ///
+    /// # Arguments
+    /// * `path_opt`: Optional path to save the WAV file. If `None`, recording is disabled.
+    /// * `spec`: Sample rate, channel count and sample format of the file.
+    pub fn with_spec(path_opt: Option<&str>, spec: WavSpec) -> Result<Self, WhisperStreamError> {
+        Self::open(path_opt, spec, false)
+    }
+
+    /// Like [`WavAudioRecorder::with_spec`], but with `append` set continues an existing file
+    /// instead of truncating it, e.g. to keep the audio of a session that crashed.
+    ///
+    /// Appending to a file whose spec differs from `spec` fails with
+    /// [`WhisperStreamError::WavWrite`] rather than mixing formats. If the file doesn't exist
+    /// yet, it is created as usual.
+    pub fn open(path_opt: Option<&str>, spec: WavSpec, append: bool) -> Result<Self, WhisperStreamError> {
+        check_recording_spec(spec)?;
         match path_opt {
             Some(p) => {
                 // Create parent directory if it doesn't exist
-                if let Some(parent_dir) = Path::new(p).parent() {
-                    if !parent_dir.exists() {
-                        fs::create_dir_all(parent_dir).map_err(|e| WhisperStreamError::Io { source: e })?;
-                    }
+                if let Some(parent_dir) = Path::new(p).parent()
+                    && !parent_dir.exists()
+                {
+                    fs::create_dir_all(parent_dir).map_err(|e| WhisperStreamError::Io { source: e })?;
                 }
 
-                let spec = WavSpec {
-                    channels: 1,        // Whisper processes mono audio
-                    sample_rate: 16000, // Whisper processes 16kHz audio
-                    bits_per_sample: 16,
-                    sample_format: SampleFormat::Int,
-                };
-                let writer = WavWriter::create(p, spec)
+                if !(append && Path::new(p).exists()) {
+                    let file = fs::File::create(p).map_err(|e| WhisperStreamError::Io { source: e })?;
+                    let mut recorder = Self::from_writer(BufWriter::new(file), spec)?;
+                    recorder.path = p.to_string();
+                    return Ok(recorder);
+                }
+
+                let reader = hound::WavReader::open(p)
                     .map_err(|e| WhisperStreamError::Hound { source: e })?;
-                Ok(Self {
-                    writer: Some(writer),
-                    path: p.to_string(),
-                    is_recording_active: true,
-                })
+                let existing = reader.spec();
+                if existing != spec {
+                    return Err(WhisperStreamError::WavWrite(format!(
+                        "Cannot append to {}: it is {}-channel {}Hz {}-bit {:?}, but {}-channel {}Hz {}-bit {:?} was requested",
+                        p, existing.channels, existing.sample_rate, existing.bits_per_sample, existing.sample_format,
+                        spec.channels, spec.sample_rate, spec.bits_per_sample, spec.sample_format
+                    )));
+                }
+                let existing_samples = reader.len() as u64;
+                let writer = WavWriter::append(p).map_err(|e| WhisperStreamError::Hound { source: e })?;
+                Ok(Self::with_writer(Some(writer), p.to_string(), existing_samples))
+            }
+            None => Ok(Self::with_writer(None, String::new(), 0)),
+        }
+    }
+
+    /// Creates a recorder writing to a new `YYYY-MM-DD_HH-MM-SS.wav` file in `dir`.
+    ///
+    /// The directory is created if needed and the timestamp is the current UTC time. If a file
+    /// with that name already exists (two recordings in the same second), `_1`, `_2`, ... is
+    /// appended so nothing is overwritten. Returns the recorder and the chosen path.
+    pub fn new_in_dir<P: AsRef<Path>>(dir: P) -> Result<(Self, PathBuf), WhisperStreamError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| WhisperStreamError::Io { source: e })?;
+
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let stem = utc_timestamp_file_stem(secs);
+        let mut path = dir.join(format!("{}.wav", stem));
+        let mut suffix = 1;
+        while path.exists() {
+            path = dir.join(format!("{}_{}.wav", stem, suffix));
+            suffix += 1;
+        }
+
+        let path_str = path.to_str()
+            .ok_or_else(|| WhisperStreamError::WavWrite(format!("Recording path is not valid UTF-8: {}", path.display())))?;
+        let recorder = Self::new(Some(path_str))?;
+        Ok((recorder, path))
+    }
+
+}
+
+impl<W: Write + Seek> WavAudioRecorder<W> {
+    /// Creates a recorder writing a WAV stream with `spec` into `writer`, e.g. a
+    /// `Cursor<Vec<u8>>` or a socket wrapper.
+    ///
+    /// The header is written immediately and its lengths are filled in by
+    /// [`WavAudioRecorder::finalize`], which is why the sink must be seekable. The sample format
+    /// rules of [`WavAudioRecorder::new_with_format`] apply.
+    pub fn from_writer(writer: W, spec: WavSpec) -> Result<Self, WhisperStreamError> {
+        check_recording_spec(spec)?;
+        let writer = WavWriter::new(writer, spec).map_err(|e| WhisperStreamError::Hound { source: e })?;
+        Ok(Self::with_writer(Some(writer), "WAV writer".to_string(), 0))
+    }
+
+    fn with_writer(writer: Option<WavWriter<W>>, path: String, samples_written: u64) -> Self {
+        Self {
+            is_recording_active: writer.is_some(),
+            writer,
+            path,
+            channel_gains: Vec::new(),
+            samples_written,
+            clipped_samples: 0,
+            warned_interleaved: false,
+            silence_gate: None,
+            skipped_samples: 0,
+            in_skipped_silence: false,
+        }
+    }
+
+    /// Stops writing chunks whose energy is at or below the gate's threshold, or writes every
+    /// chunk again with `None`.
+    ///
+    /// Whole chunks are kept or dropped, so feed chunks of a few tens of milliseconds for the
+    /// gate to follow speech closely. The dropped audio is reported by
+    /// [`WavAudioRecorder::skipped_silence_secs`] and [`WavAudioRecorder::finalize`].
+    pub fn set_silence_gate(&mut self, gate: Option<SilenceGate>) {
+        self.silence_gate = gate;
+    }
+
+    /// Returns the duration of the silent chunks dropped by the silence gate, in seconds.
+    pub fn skipped_silence_secs(&self) -> f64 {
+        match self.writer.as_ref() {
+            Some(writer) => {
+                let spec = writer.spec();
+                self.skipped_samples as f64 / (spec.sample_rate as f64 * spec.channels as f64)
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Runs a chunk of `len` samples with RMS energy `energy` through the silence gate and
+    /// returns whether to write it. Before the first chunk kept after dropped silence, the
+    /// gate's marker is written.
+    fn pass_silence_gate(&mut self, energy: f32, len: usize) -> Result<bool, WhisperStreamError> {
+        let (Some(gate), Some(writer)) = (self.silence_gate, self.writer.as_mut()) else {
+            return Ok(true);
+        };
+        if energy <= gate.energy_threshold {
+            self.skipped_samples += len as u64;
+            self.in_skipped_silence = true;
+            return Ok(false);
+        }
+        // Only mark gaps between retained segments, not silence before the first one.
+        if std::mem::take(&mut self.in_skipped_silence) && self.samples_written > 0 {
+            let spec = writer.spec();
+            for _ in 0..gate.marker_frames * spec.channels as usize {
+                let written = match spec.sample_format {
+                    SampleFormat::Float => writer.write_sample(0.0f32),
+                    SampleFormat::Int => writer.write_sample(0i16),
+                };
+                written.map_err(|e| WhisperStreamError::Hound { source: e })?;
+                self.samples_written += 1;
             }
-            None => Ok(Self {
-                writer: None,
-                path: String::new(),
-                is_recording_active: false,
-            }),
         }
+        Ok(true)
+    }
+
+    /// Sets a gain per channel, applied in `write_audio_chunk` before quantization.
+    ///
+    /// `gains[c]` scales every sample of channel `c` in interleaved input, e.g. to balance a
+    /// hotter microphone in a two-mic rig. Gains outside `0.0..=MAX_CHANNEL_GAIN` are clamped
+    /// with a warning. An empty slice restores unity gain.
+    pub fn set_channel_gains(&mut self, gains: &[f32]) -> Result<(), WhisperStreamError> {
+        if let Some(writer) = self.writer.as_ref() {
+            let channels = writer.spec().channels as usize;
+            if !gains.is_empty() && gains.len() != channels {
+                return Err(WhisperStreamError::WavWrite(format!(
+                    "Expected {} channel gains for a {}-channel recording, got {}", channels, channels, gains.len()
+                )));
+            }
+        }
+        self.channel_gains = gains.iter().enumerate().map(|(channel, &gain)| {
+            let clamped = if gain.is_finite() { gain.clamp(0.0, MAX_CHANNEL_GAIN) } else { 1.0 };
+            if clamped != gain {
+                warn!("[WAV Writer] Gain {} for channel {} is out of range; using {}.", gain, channel, clamped);
+            }
+            clamped
+        }).collect();
+        Ok(())
     }
 
     /// Writes an audio chunk to the WAV file if recording is active.
     ///
     /// # Arguments
-    /// * `audio_chunk`: A slice of `f32` audio samples (expected to be mono, 16kHz).
+    /// * `audio_chunk`: A slice of `f32` audio samples at the recorder's sample rate,
+    ///   interleaved if it has more than one channel (mono 16kHz by default).
     ///
 	/// Samples should be in the range -1.0 to 1.0.
+    ///
+    /// In debug builds, a mono recording warns once if a chunk looks like interleaved stereo
+    /// (see [`looks_like_interleaved_stereo`]), which would play back garbled at half speed.
+    ///
+    /// With a [`SilenceGate`] set, a silent chunk is dropped instead of written.
     pub fn write_audio_chunk(&mut self, audio_chunk: &[f32]) -> Result<(), WhisperStreamError> {
+        if self.writer.is_some() && !self.pass_silence_gate(rms(audio_chunk), audio_chunk.len())? {
+            return Ok(());
+        }
         if let Some(writer) = self.writer.as_mut() {
+            let sample_format = writer.spec().sample_format;
+            if cfg!(debug_assertions) && writer.spec().channels == 1 && !self.warned_interleaved
+                && looks_like_interleaved_stereo(audio_chunk)
+            {
+                warn!("[WAV Writer] Chunk written to mono recording {} looks like interleaved stereo; downmix it first (see downmix_to_mono).", self.path);
+                self.warned_interleaved = true;
+            }
             let mut min_sample = f32::INFINITY;
             let mut max_sample = f32::NEG_INFINITY;
             let mut non_zero_count = 0;
+            let mut clipped = 0;
 
-            for &sample_f32_original in audio_chunk {
+            for (index, &sample_f32_original) in audio_chunk.iter().enumerate() {
                 min_sample = min_sample.min(sample_f32_original);
                 max_sample = max_sample.max(sample_f32_original);
                 if sample_f32_original != 0.0 {
@@ -95,35 +979,150 @@ impl WavAudioRecorder {
                     warn!("Non-finite audio sample detected: {}. Replacing with 0.0.", sample_f32_original);
                     0.0
                 };
+                let sample_f32 = match self.channel_gains.len() {
+                    0 => sample_f32,
+                    n => sample_f32 * self.channel_gains[index % n],
+                };
 
-                // Clamp to [-1.0, 1.0) then scale and cast
-                let clamped_sample = sample_f32.clamp(-1.0, 1.0 - f32::EPSILON);
-                // Scale to i16 range and round to nearest integer
-                let scaled = clamped_sample * i16::MAX as f32;
-                let sample_i16 = scaled.round() as i16;
-                if let Err(e) = writer.write_sample(sample_i16) {
+                let written = match sample_format {
+                    SampleFormat::Float => writer.write_sample(sample_f32),
+                    SampleFormat::Int => {
+                        if sample_f32.abs() > 1.0 {
+                            clipped += 1;
+                        }
+                        // Clamp to [-1.0, 1.0) then scale and cast
+                        let clamped_sample = sample_f32.clamp(-1.0, 1.0 - f32::EPSILON);
+                        // Scale to i16 range and round to nearest integer
+                        let scaled = clamped_sample * i16::MAX as f32;
+                        writer.write_sample(scaled.round() as i16)
+                    }
+                };
+                if let Err(e) = written {
                     return Err(WhisperStreamError::Hound { source: e });
                 }
+                self.samples_written += 1;
             }
 
-            debug!("[WAV Writer] Chunk stats: len={}, non_zero={}, range=[{:.6}, {:.6}]",
-                audio_chunk.len(), non_zero_count, min_sample, max_sample);
+            debug!("[WAV Writer] Chunk stats: len={}, non_zero={}, clipped={}, range=[{:.6}, {:.6}]",
+                audio_chunk.len(), non_zero_count, clipped, min_sample, max_sample);
+            self.record_clipping(clipped, audio_chunk.len());
         }
         Ok(())
     }
 
-    /// Finalizes the WAV file. Must be called to complete the recording.
-    /// Returns a system message indicating the result.
-    pub fn finalize(mut self) -> Result<Option<String>, WhisperStreamError> {
-        // Use a match statement for clearer logic based on the state.
-        // self.writer is taken, so it becomes None after the first call or if initially None.
-        match (self.writer.take(), self.is_recording_active, !self.path.is_empty()) {
-            (Some(writer), true, true) => {
-                // Active recording, valid path, writer exists: finalize and report success.
-                writer.finalize().map_err(|e| WhisperStreamError::Hound { source: e })?;
-                Ok(Some(format!("[Recording] Finished saving audio to {}", self.path)))
-            }
-            (Some(writer), _, _) => {
+    /// Adds `clipped` of a chunk's `len` samples to the count, warning if too many clipped.
+    fn record_clipping(&mut self, clipped: u64, len: usize) {
+        self.clipped_samples += clipped;
+        if clipped as f64 > CLIPPING_WARN_RATIO * len as f64 {
+            warn!("[WAV Writer] {} of {} samples clipped while recording {}; lower the input gain, clipping hurts transcription.",
+                clipped, len, self.path);
+        }
+    }
+
+    /// Returns how many samples were clamped because they exceeded full scale, across all
+    /// chunks written.
+    ///
+    /// Only 16-bit integer recordings clip: 32-bit float ones store out-of-range samples as
+    /// they are. More than 0.1% of a chunk clipping also logs a warning.
+    pub fn clipped_sample_count(&self) -> u64 {
+        self.clipped_samples
+    }
+
+    /// Writes a chunk of 16-bit PCM samples to the WAV file if recording is active.
+    ///
+    /// For backends that already capture `i16`: in the default 16-bit integer mode the samples
+    /// are written exactly as given, with no round trip through f32. Channel gains, if set, are
+    /// still applied (saturating at the i16 range). A 32-bit float recorder stores each sample
+    /// divided by 32768. A [`SilenceGate`] judges the samples scaled the same way.
+    pub fn write_audio_chunk_i16(&mut self, audio_chunk: &[i16]) -> Result<(), WhisperStreamError> {
+        if self.writer.is_some() && self.silence_gate.is_some() {
+            let scaled: Vec<f32> = audio_chunk.iter().map(|&s| s as f32 / 32768.0).collect();
+            if !self.pass_silence_gate(rms(&scaled), audio_chunk.len())? {
+                return Ok(());
+            }
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        let sample_format = writer.spec().sample_format;
+        let mut clipped = 0;
+        for (index, &sample) in audio_chunk.iter().enumerate() {
+            let gain = match self.channel_gains.len() {
+                0 => None,
+                n => Some(self.channel_gains[index % n]),
+            };
+            let written = match (sample_format, gain) {
+                (SampleFormat::Int, None) => writer.write_sample(sample),
+                (SampleFormat::Int, Some(gain)) => {
+                    let scaled = (sample as f32 * gain).round();
+                    if !(i16::MIN as f32..=i16::MAX as f32).contains(&scaled) {
+                        clipped += 1;
+                    }
+                    writer.write_sample(scaled.clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+                }
+                (SampleFormat::Float, gain) => writer.write_sample(sample as f32 / 32768.0 * gain.unwrap_or(1.0)),
+            };
+            written.map_err(|e| WhisperStreamError::Hound { source: e })?;
+            self.samples_written += 1;
+        }
+        debug!("[WAV Writer] Wrote {} i16 samples.", audio_chunk.len());
+        self.record_clipping(clipped, audio_chunk.len());
+        Ok(())
+    }
+
+    /// Like [`WavAudioRecorder::write_audio_chunk`], but states how many interleaved channels
+    /// `audio_chunk` has and fails with [`WhisperStreamError::WavWrite`] if that doesn't match
+    /// the recording, e.g. stereo device audio passed to the default mono recorder, or if the
+    /// chunk doesn't hold whole frames.
+    pub fn write_interleaved_chunk(&mut self, audio_chunk: &[f32], channels: u16) -> Result<(), WhisperStreamError> {
+        if let Some(writer) = self.writer.as_ref() {
+            let expected = writer.spec().channels;
+            if channels != expected {
+                return Err(WhisperStreamError::WavWrite(format!(
+                    "Chunk has {} interleaved channels but {} is a {}-channel recording", channels, self.path, expected
+                )));
+            }
+            if !audio_chunk.len().is_multiple_of(channels as usize) {
+                return Err(WhisperStreamError::WavWrite(format!(
+                    "Chunk of {} samples doesn't hold whole {}-channel frames", audio_chunk.len(), channels
+                )));
+            }
+        }
+        self.write_audio_chunk(audio_chunk)
+    }
+
+    /// Returns the duration of the audio written so far, in seconds.
+    ///
+    /// Compare it with the expected recording length to spot dropped buffers. In append mode
+    /// this includes the audio already in the file. Always `0.0` when recording is disabled.
+    pub fn duration_secs(&self) -> f64 {
+        match self.writer.as_ref() {
+            Some(writer) => {
+                let spec = writer.spec();
+                self.samples_written as f64 / (spec.sample_rate as f64 * spec.channels as f64)
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Finalizes the WAV file. Must be called to complete the recording.
+    /// Returns a system message indicating the result, including any silence the
+    /// [`SilenceGate`] skipped.
+    pub fn finalize(mut self) -> Result<Option<String>, WhisperStreamError> {
+        let duration_secs = self.duration_secs();
+        let skipped = match self.skipped_silence_secs() {
+            secs if secs > 0.0 => format!(" ({:.2}s of silence skipped)", secs),
+            _ => String::new(),
+        };
+        // Use a match statement for clearer logic based on the state.
+        // self.writer is taken, so it becomes None after the first call or if initially None.
+        match (self.writer.take(), self.is_recording_active, !self.path.is_empty()) {
+            (Some(writer), true, true) => {
+                // Active recording, valid path, writer exists: finalize and report success.
+                writer.finalize().map_err(|e| WhisperStreamError::Hound { source: e })?;
+                Ok(Some(format!("[Recording] Finished saving {:.2}s of audio to {}{}", duration_secs, self.path, skipped)))
+            }
+            (Some(writer), _, _) => {
                 // Writer existed but state was inconsistent (e.g. not active or no path), still try to finalize.
                 // This case helps ensure the file is closed if it was opened.
                 writer.finalize().map_err(|e| WhisperStreamError::Hound { source: e })?;
@@ -149,6 +1148,263 @@ impl WavAudioRecorder {
     }
 }
 
+/// In-memory recorder that keeps only the most recent `capacity_samples` of audio.
+///
+/// Useful as a pre-roll buffer: feed it every chunk, and when speech is detected take a
+/// [`RingBufferRecorder::snapshot`] to recover the audio from just before it started.
+#[derive(Debug, Clone)]
+pub struct RingBufferRecorder {
+    buffer: VecDeque<f32>,
+    capacity_samples: usize,
+}
+
+impl RingBufferRecorder {
+    /// Creates a new `RingBufferRecorder` retaining at most `capacity_samples` samples.
+    pub fn new(capacity_samples: usize) -> Self {
+        Self { buffer: VecDeque::with_capacity(capacity_samples), capacity_samples }
+    }
+
+    /// Appends `audio_chunk`, dropping the oldest samples beyond the capacity.
+    pub fn write_audio_chunk(&mut self, audio_chunk: &[f32]) {
+        // Only the tail of an oversized chunk can survive.
+        let chunk = &audio_chunk[audio_chunk.len().saturating_sub(self.capacity_samples)..];
+        let overflow = (self.buffer.len() + chunk.len()).saturating_sub(self.capacity_samples);
+        self.buffer.drain(..overflow);
+        self.buffer.extend(chunk);
+    }
+
+    /// Returns the retained samples, oldest first.
+    pub fn snapshot(&self) -> Vec<f32> {
+        self.buffer.iter().copied().collect()
+    }
+
+    /// Returns the number of samples currently retained.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if no samples are retained.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Discards all retained samples.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+/// Configures a [`WavAudioRecorder`] step by step.
+///
+/// ```no_run
+/// use whisper_stream_rs::{SampleFormat, WavAudioRecorder};
+///
+/// let recorder = WavAudioRecorder::builder()
+///     .path("session.wav")
+///     .sample_rate(48000)
+///     .channels(2)
+///     .sample_format(SampleFormat::Float, 32)
+///     .append(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WavAudioRecorderBuilder {
+    path: Option<String>,
+    sample_rate: u32,
+    channels: u16,
+    bits_per_sample: u16,
+    sample_format: SampleFormat,
+    append: bool,
+    silence_gate: Option<SilenceGate>,
+}
+
+impl WavAudioRecorderBuilder {
+    /// Records to `path`. Without a path, the built recorder discards everything.
+    pub fn path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+    /// Sample rate written to the file header (default 16 kHz, what Whisper expects).
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+    /// Number of channels; chunks with more than one are interleaved.
+    pub fn channels(mut self, channels: u16) -> Self {
+        self.channels = channels;
+        self
+    }
+    /// Sample encoding: 16 bits for `SampleFormat::Int`, 32 for `SampleFormat::Float`.
+    pub fn sample_format(mut self, sample_format: SampleFormat, bits_per_sample: u16) -> Self {
+        self.sample_format = sample_format;
+        self.bits_per_sample = bits_per_sample;
+        self
+    }
+    /// Continues an existing file instead of truncating it; see [`WavAudioRecorder::open`].
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+    /// Drops silent chunks; see [`WavAudioRecorder::set_silence_gate`].
+    pub fn silence_gate(mut self, gate: SilenceGate) -> Self {
+        self.silence_gate = Some(gate);
+        self
+    }
+
+    /// Creates the recorder, failing with [`WhisperStreamError::WavWrite`] for an unsupported
+    /// sample format, zero channels or sample rate, or an append target with a different spec.
+    pub fn build(self) -> Result<WavAudioRecorder, WhisperStreamError> {
+        let spec = WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: self.bits_per_sample,
+            sample_format: self.sample_format,
+        };
+        let mut recorder = WavAudioRecorder::open(self.path.as_deref(), spec, self.append)?;
+        recorder.set_silence_gate(self.silence_gate);
+        Ok(recorder)
+    }
+}
+
+/// Fewest samples [`looks_like_interleaved_stereo`] judges; shorter chunks are too noisy.
+const MIN_INTERLEAVE_CHECK_SAMPLES: usize = 512;
+
+/// Whether `samples`, supposedly mono, are more likely two interleaved channels.
+///
+/// In mono audio, the difference between samples `2k` and `2k + 1` is statistically the same
+/// as between `2k + 1` and `2k + 2`. When two closely matching channels are interleaved, as
+/// from a mono microphone exposed as a stereo device, each pair holds the same instant twice,
+/// so the first difference is far smaller. Stereo with clearly different channels is not
+/// detected. Silent and short (< 512 samples) input is never flagged.
+pub fn looks_like_interleaved_stereo(samples: &[f32]) -> bool {
+    if samples.len() < MIN_INTERLEAVE_CHECK_SAMPLES {
+        return false;
+    }
+    let (mut within_pairs, mut across_pairs) = (0.0f64, 0.0f64);
+    for frame in samples.windows(3).step_by(2) {
+        within_pairs += ((frame[0] - frame[1]) as f64).powi(2);
+        across_pairs += ((frame[1] - frame[2]) as f64).powi(2);
+    }
+    across_pairs > 1e-9 * samples.len() as f64 && within_pairs < 0.25 * across_pairs
+}
+
+/// Splits a long recording across numbered WAV files of bounded duration or size.
+///
+/// Files are named `<prefix>-0001.wav`, `<prefix>-0002.wav`, ... in the target directory.
+/// Once the current file reaches the duration or byte limit, it is finalized and the next
+/// chunk starts a new file. Rotation happens between chunks, so a file may exceed the limit
+/// by up to one chunk.
+pub struct RotatingWavRecorder {
+    dir: PathBuf,
+    prefix: String,
+    spec: WavSpec,
+    max_samples: Option<u64>,
+    max_bytes: Option<u64>,
+    current: Option<(WavAudioRecorder, PathBuf)>,
+    current_samples: u64,
+    next_index: u32,
+    finished: Vec<PathBuf>,
+}
+
+impl RotatingWavRecorder {
+    /// Creates a new `RotatingWavRecorder`. The directory is created if needed.
+    ///
+    /// # Arguments
+    /// * `dir`: Directory to write the numbered files to.
+    /// * `prefix`: File name prefix, e.g. `"capture"`.
+    /// * `spec`: Format of every file, as for [`WavAudioRecorder::with_spec`].
+    /// * `max_duration`: Rotate once a file holds this much audio, or `None` for no limit.
+    /// * `max_bytes`: Rotate once a file's sample data reaches this size, or `None` for no limit.
+    pub fn new<P: AsRef<Path>>(dir: P, prefix: &str, spec: WavSpec, max_duration: Option<Duration>, max_bytes: Option<u64>) -> Result<Self, WhisperStreamError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| WhisperStreamError::Io { source: e })?;
+        let max_samples = max_duration
+            .map(|d| (d.as_secs_f64() * spec.sample_rate as f64) as u64 * spec.channels as u64);
+        Ok(Self {
+            dir,
+            prefix: prefix.to_string(),
+            spec,
+            max_samples,
+            max_bytes,
+            current: None,
+            current_samples: 0,
+            next_index: 1,
+            finished: Vec::new(),
+        })
+    }
+
+    /// Writes an audio chunk to the current file, rotating to a new file if a limit is reached.
+    pub fn write_audio_chunk(&mut self, audio_chunk: &[f32]) -> Result<(), WhisperStreamError> {
+        if audio_chunk.is_empty() {
+            return Ok(());
+        }
+        if self.current.is_none() {
+            let path = self.dir.join(format!("{}-{:04}.wav", self.prefix, self.next_index));
+            let path_str = path.to_str()
+                .ok_or_else(|| WhisperStreamError::WavWrite(format!("Recording path is not valid UTF-8: {}", path.display())))?;
+            let recorder = WavAudioRecorder::with_spec(Some(path_str), self.spec)?;
+            self.current = Some((recorder, path));
+            self.current_samples = 0;
+            self.next_index += 1;
+        }
+        if let Some((recorder, _)) = self.current.as_mut() {
+            recorder.write_audio_chunk(audio_chunk)?;
+        }
+        self.current_samples += audio_chunk.len() as u64;
+
+        let bytes = self.current_samples * (self.spec.bits_per_sample as u64 / 8);
+        let full = self.max_samples.is_some_and(|max| self.current_samples >= max)
+            || self.max_bytes.is_some_and(|max| bytes >= max);
+        if full {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the files finalized so far, in order.
+    pub fn finished_paths(&self) -> &[PathBuf] {
+        &self.finished
+    }
+
+    /// Finalizes the current file and returns the paths of all files written, in order.
+    pub fn finalize(mut self) -> Result<Vec<PathBuf>, WhisperStreamError> {
+        self.rotate()?;
+        Ok(self.finished)
+    }
+
+    fn rotate(&mut self) -> Result<(), WhisperStreamError> {
+        if let Some((recorder, path)) = self.current.take() {
+            recorder.finalize()?;
+            debug!("[WAV Writer] Rotated recording file {}", path.display());
+            self.finished.push(path);
+        }
+        Ok(())
+    }
+}
+
+/// Formats seconds since the Unix epoch as a `YYYY-MM-DD_HH-MM-SS` UTC file stem.
+fn utc_timestamp_file_stem(secs_since_epoch: u64) -> String {
+    let days = (secs_since_epoch / 86_400) as i64;
+    let secs_of_day = secs_since_epoch % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year, month, day, secs_of_day / 3600, (secs_of_day / 60) % 60, secs_of_day % 60
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -163,6 +1419,55 @@ mod tests {
         assert!(matches!(result, std::borrow::Cow::Borrowed(_)));
     }
 
+    #[test]
+    fn test_utc_timestamp_file_stem() {
+        assert_eq!(utc_timestamp_file_stem(0), "1970-01-01_00-00-00");
+        assert_eq!(utc_timestamp_file_stem(951_782_400 + 3_723), "2000-02-29_01-02-03");
+        assert_eq!(utc_timestamp_file_stem(1_735_689_599), "2024-12-31_23-59-59");
+    }
+
+    #[test]
+    fn test_wav_recorder_new_in_dir_picks_unique_names() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-recordings");
+        let _ = fs::remove_dir_all(&dir);
+        let (first, first_path) = WavAudioRecorder::new_in_dir(&dir).unwrap();
+        let (second, second_path) = WavAudioRecorder::new_in_dir(&dir).unwrap();
+        assert!(first.is_recording() && second.is_recording());
+        assert_ne!(first_path, second_path);
+        assert_eq!(first_path.parent(), Some(dir.as_path()));
+        assert_eq!(first_path.extension().and_then(|e| e.to_str()), Some("wav"));
+        first.finalize().unwrap();
+        second.finalize().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(feature = "microphone")]
+    #[test]
+    fn test_pad_audio_into_reuses_scratch() {
+        let mut scratch = Vec::with_capacity(8);
+        let capacity_ptr = scratch.as_ptr();
+        assert_eq!(pad_audio_into(&[0.1, 0.2], 4, &mut scratch), &[0.1, 0.2, 0.0, 0.0]);
+        assert_eq!(pad_audio_into(&[0.3], 2, &mut scratch), &[0.3, 0.0]);
+        assert_eq!(scratch.as_ptr(), capacity_ptr);
+        assert_eq!(pad_audio_into(&[0.1, 0.2, 0.3], 2, &mut scratch), &[0.1, 0.2, 0.3]);
+    }
+
+    #[cfg(feature = "microphone")]
+    #[test]
+    fn test_buffer_pool_recycles_buffers() {
+        let pool = BufferPool::new(1);
+        let mut buffer = pool.take();
+        buffer.extend_from_slice(&[1.0; 100]);
+        let ptr = buffer.as_ptr();
+        pool.give(buffer);
+        pool.give(vec![0.0; 10]); // Over the limit; dropped.
+
+        let reused = pool.take();
+        assert!(reused.is_empty());
+        assert_eq!(reused.as_ptr(), ptr);
+        assert_eq!(pool.take().capacity(), 0);
+    }
+
     #[test]
     fn test_pad_audio_if_needed_with_padding() {
         let input = vec![0.1, 0.2];
@@ -171,6 +1476,171 @@ mod tests {
         assert!(matches!(result, std::borrow::Cow::Owned(_)));
     }
 
+    #[test]
+    fn test_pad_audio_silence_vs_edge() {
+        let input = [0.1, 0.4];
+        assert_eq!(&*pad_audio_if_needed(&input, 4), &[0.1, 0.4, 0.0, 0.0]);
+        assert_eq!(&*pad_audio_with_value(&input, 4, 0.0), &*pad_audio_if_needed(&input, 4));
+        assert_eq!(&*pad_audio_with_value(&input, 4, -0.5), &[0.1, 0.4, -0.5, -0.5]);
+        assert_eq!(&*pad_audio_edge(&input, 4), &[0.1, 0.4, 0.4, 0.4]);
+        assert_eq!(&*pad_audio_edge(&[], 2), &[0.0, 0.0]);
+        assert!(matches!(pad_audio_edge(&input, 2), std::borrow::Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_pad_audio_with_mode() {
+        let input = [0.1, 0.2];
+        assert_eq!(&*pad_audio_with_mode(&input, 5, PadMode::End), &[0.1, 0.2, 0.0, 0.0, 0.0]);
+        assert_eq!(&*pad_audio_with_mode(&input, 5, PadMode::Start), &[0.0, 0.0, 0.0, 0.1, 0.2]);
+        // The odd padding sample goes to the end.
+        assert_eq!(&*pad_audio_with_mode(&input, 5, PadMode::Center), &[0.0, 0.1, 0.2, 0.0, 0.0]);
+        assert_eq!(&*pad_audio_with_mode(&input, 4, PadMode::Center), &[0.0, 0.1, 0.2, 0.0]);
+        for mode in [PadMode::End, PadMode::Start, PadMode::Center] {
+            assert!(matches!(pad_audio_with_mode(&input, 2, mode), std::borrow::Cow::Borrowed(_)));
+        }
+    }
+
+    #[test]
+    fn test_coalesce_segments() {
+        let ranges = vec![3000..4000, 0..1000, 1100..2000, 1500..2500, 6000..7000];
+        assert_eq!(coalesce_segments(&ranges, 200), vec![0..2500, 3000..4000, 6000..7000]);
+        assert_eq!(coalesce_segments(&ranges, 500), vec![0..4000, 6000..7000]);
+        assert_eq!(coalesce_segments(&[0..10, 10..20], 0), vec![0..20]);
+        assert!(coalesce_segments(&[], 100).is_empty());
+    }
+
+    #[test]
+    fn test_concat_segments() {
+        let joined = concat_segments(&[&[0.5, 0.5], &[-0.25, -0.25, -0.25]], 3);
+        assert_eq!(joined.len(), 8);
+        assert_eq!(joined, vec![0.5, 0.5, 0.0, 0.0, 0.0, -0.25, -0.25, -0.25]);
+        assert_eq!(concat_segments(&[&[0.1, 0.2]], 100), vec![0.1, 0.2]);
+        assert!(concat_segments(&[], 100).is_empty());
+    }
+
+    #[test]
+    fn test_measure_lufs_sine_reference() {
+        // A full-scale 997Hz sine in one channel measures -3.01 LUFS.
+        let sine = |amplitude: f32| -> Vec<f32> {
+            (0..48000 * 3)
+                .map(|i| amplitude * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / 48000.0).sin())
+                .collect()
+        };
+        assert!((measure_lufs(&sine(1.0), 48000) + 3.01).abs() < 0.1);
+        assert!((measure_lufs(&sine(0.5), 48000) + 9.03).abs() < 0.1);
+        assert_eq!(measure_lufs(&vec![0.0; 16000], 16000), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_agc_reduces_loud_and_boosts_quiet_input() {
+        let mut agc = Agc::new(0.1, 4.0);
+        for _ in 0..50 {
+            let mut loud = vec![0.8f32; 1600];
+            agc.process(&mut loud);
+        }
+        assert!(agc.gain() < 0.2);
+
+        let mut agc = Agc::new(0.1, 4.0);
+        for _ in 0..200 {
+            let mut quiet = vec![0.001f32; 1600];
+            agc.process(&mut quiet);
+        }
+        assert!(agc.gain() <= 4.0);
+        assert!(agc.gain() > 3.9);
+    }
+
+    #[test]
+    fn test_agc_holds_gain_on_silence() {
+        let mut agc = Agc::new(0.1, 4.0);
+        let mut silence = vec![0.0f32; 1600];
+        agc.process(&mut silence);
+        assert_eq!(agc.gain(), 1.0);
+        assert!(silence.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_normalize_peak() {
+        let mut samples = vec![0.1, -0.25, 0.2, 0.0];
+        normalize_peak(&mut samples, 1.0);
+        assert_eq!(samples.iter().fold(0.0f32, |max, s| max.max(s.abs())), 1.0);
+        assert_eq!(samples, vec![0.4, -1.0, 0.8, 0.0]);
+
+        let mut silence = vec![0.0; 4];
+        normalize_peak(&mut silence, 1.0);
+        assert_eq!(silence, vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_apply_fade() {
+        let mut samples = vec![1.0; 10];
+        apply_fade(&mut samples, 4);
+        assert_eq!(samples, vec![0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 0.75, 0.5, 0.25, 0.0]);
+
+        // Clamped to half the buffer.
+        let mut short = vec![1.0; 4];
+        apply_fade(&mut short, 100);
+        assert_eq!(short, vec![0.0, 0.5, 0.5, 0.0]);
+        apply_fade(&mut [], 4);
+    }
+
+    #[test]
+    fn test_apply_gain_clamps() {
+        let mut samples = vec![0.25, -0.5, 0.75];
+        apply_gain(&mut samples, 2.0);
+        assert_eq!(samples, vec![0.5, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_is_speech() {
+        assert!(is_speech(&[0.5, -0.5, 0.5, -0.5], 0.1));
+        assert!(!is_speech(&[0.01, -0.01, 0.0, 0.02], 0.1));
+        assert!(!is_speech(&[], 0.1));
+    }
+
+    #[test]
+    fn test_silence_detector_fires_after_speech_then_silence() {
+        let loud = [0.5f32; 160];
+        let quiet = [0.0f32; 160];
+        let mut detector = SilenceDetector::new(0.1, 400);
+        // Leading silence never fires.
+        assert!(!detector.process_frame(&quiet));
+        assert!(!detector.process_frame(&quiet));
+        assert!(!detector.process_frame(&quiet));
+
+        assert!(!detector.process_frame(&loud));
+        assert!(!detector.process_frame(&quiet));
+        assert!(!detector.process_frame(&quiet));
+        assert_eq!(detector.silent_samples(), 320);
+        assert!(detector.process_frame(&quiet));
+        // Fires only once per silence.
+        assert!(!detector.process_frame(&quiet));
+
+        // Speech resets the silence count.
+        assert!(!detector.process_frame(&loud));
+        assert!(!detector.process_frame(&quiet));
+        assert!(!detector.process_frame(&loud));
+        assert_eq!(detector.silent_samples(), 0);
+    }
+
+    #[test]
+    fn test_vad_decisions_independent_of_chunking() {
+        // 100ms of tone, 200ms of silence, 50ms of tone, 300ms of silence at 16kHz.
+        let mut signal = Vec::new();
+        for (len, amplitude) in [(1600, 0.5f32), (3200, 0.0), (800, 0.5), (4800, 0.0)] {
+            signal.extend((0..len).map(|i| if i % 2 == 0 { amplitude } else { -amplitude }));
+        }
+
+        let mut whole = Vad::new(480, 0.05, 3);
+        let expected = whole.process(&signal);
+
+        for chunk_len in [1, 7, 333, 480, 1000] {
+            let mut chunked = Vad::new(480, 0.05, 3);
+            let decisions: Vec<bool> = signal.chunks(chunk_len).flat_map(|c| chunked.process(c)).collect();
+            assert_eq!(decisions, expected, "chunk_len={}", chunk_len);
+        }
+        assert!(expected.contains(&true) && expected.contains(&false));
+    }
+
     #[test]
     fn test_wav_audio_recorder_write_and_finalize() {
         let test_path = "test_output.wav";
@@ -187,6 +1657,507 @@ mod tests {
         let _ = fs::remove_file(test_path);
     }
 
+    #[test]
+    fn test_read_wav_i16_round_trips_recorder_output() {
+        let test_path = "test_read_wav_i16.wav";
+        let _ = fs::remove_file(test_path);
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.0, 0.5, -0.5]).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let (samples, spec) = read_wav_i16(test_path).expect("Failed to read WAV");
+        assert_eq!(spec.bits_per_sample, 16);
+        assert_eq!(samples, vec![0, 16384, -16384]);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_read_wav_i16_rejects_float_files_readably() {
+        let test_path = "test_read_wav_i16_float.wav";
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        write_test_wav(test_path, spec, &[0.25f32]);
+
+        let err = read_wav_i16(test_path).unwrap_err();
+        assert!(matches!(err, WhisperStreamError::UnsupportedAudioFormat { .. }));
+        assert_eq!(err.to_string(), "Unsupported audio format: 32-bit Float WAV with 1 channel(s) at 16000Hz; expected 16-bit Int");
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_write_audio_chunk_i16_is_bit_exact() {
+        let test_path = "test_write_audio_chunk_i16.wav";
+        let _ = fs::remove_file(test_path);
+        let input: Vec<i16> = vec![i16::MIN, -12345, -1, 0, 1, 12345, i16::MAX];
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        recorder.write_audio_chunk_i16(&input).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let (samples, _) = read_wav_i16(test_path).expect("Failed to read WAV");
+        assert_eq!(samples, input);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_write_audio_chunk_i16_to_float_recorder() {
+        let test_path = "test_write_audio_chunk_i16_float.wav";
+        let _ = fs::remove_file(test_path);
+        let mut recorder = WavAudioRecorder::new_with_format(Some(test_path), SampleFormat::Float, 32).expect("Failed to create recorder");
+        recorder.write_audio_chunk_i16(&[i16::MIN, 16384]).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let audio = read_wav_as_f32(test_path).expect("Failed to read WAV");
+        assert_eq!(audio.samples, vec![-1.0, 0.5]);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_builder() {
+        let test_path = "test_wav_audio_recorder_builder.wav";
+        let _ = fs::remove_file(test_path);
+        let mut recorder = WavAudioRecorder::builder().path(test_path).build().expect("Failed to build recorder");
+        recorder.write_audio_chunk(&[0.5, -0.5]).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+        let reader = hound::WavReader::open(test_path).unwrap();
+        assert_eq!(reader.spec(), WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 16, sample_format: SampleFormat::Int });
+
+        // Appends stereo float frames to a fresh file.
+        let _ = fs::remove_file(test_path);
+        let builder = WavAudioRecorder::builder()
+            .path(test_path)
+            .sample_rate(48000)
+            .channels(2)
+            .sample_format(SampleFormat::Float, 32)
+            .append(true);
+        for _ in 0..2 {
+            let mut recorder = builder.clone().build().expect("Failed to build recorder");
+            recorder.write_audio_chunk(&[0.25, -0.25]).expect("Failed to write chunk");
+            recorder.finalize().expect("Failed to finalize");
+        }
+        let mut reader = hound::WavReader::open(test_path).unwrap();
+        assert_eq!((reader.spec().channels, reader.spec().sample_rate), (2, 48000));
+        let samples: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+        assert_eq!(samples, vec![0.25, -0.25, 0.25, -0.25]);
+
+        // Float samples must be 32-bit; nothing is created.
+        let _ = fs::remove_file(test_path);
+        let invalid = WavAudioRecorder::builder().path(test_path).sample_format(SampleFormat::Float, 16).build();
+        assert!(matches!(invalid, Err(WhisperStreamError::WavWrite(_))));
+        assert!(!Path::new(test_path).exists());
+
+        // Without a path, recording stays disabled.
+        assert!(!WavAudioRecorder::builder().build().expect("Failed to build recorder").is_recording());
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_from_writer() {
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        let mut recorder = WavAudioRecorder::from_writer(&mut buffer, spec).expect("Failed to create recorder");
+        assert!(recorder.is_recording());
+        recorder.write_audio_chunk(&[0.0, 0.5, -0.5]).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let bytes = buffer.into_inner();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..12], b"WAVE");
+        let reader = hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.spec(), spec);
+        assert_eq!(reader.len(), 3);
+
+        let bad_spec = WavSpec { bits_per_sample: 24, ..spec };
+        let result = WavAudioRecorder::from_writer(std::io::Cursor::new(Vec::new()), bad_spec);
+        assert!(matches!(result, Err(WhisperStreamError::WavWrite(_))));
+    }
+
+    #[test]
+    fn test_silence_gate_drops_silent_chunks() {
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        let mut recorder = WavAudioRecorder::from_writer(&mut buffer, spec).expect("Failed to create recorder");
+        recorder.set_silence_gate(Some(SilenceGate { energy_threshold: 0.01, marker_frames: 2 }));
+        let loud = [0.5, -0.5, 0.5, -0.5];
+        let silent = [0.001, -0.001, 0.0, 0.0];
+        for chunk in [&silent, &loud, &silent, &silent, &loud, &silent, &loud] {
+            recorder.write_audio_chunk(chunk).expect("Failed to write chunk");
+        }
+        assert!((recorder.skipped_silence_secs() - 16.0 / 16000.0).abs() < 1e-12);
+        let message = recorder.finalize().expect("Failed to finalize").unwrap();
+        assert!(message.contains("silence skipped"), "{}", message);
+
+        let mut reader = hound::WavReader::new(std::io::Cursor::new(buffer.into_inner())).unwrap();
+        let samples: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        // Leading silence leaves no marker; each later gap becomes a two-frame marker.
+        let mut expected = loud.to_vec();
+        for _ in 0..2 {
+            expected.extend([0.0, 0.0]);
+            expected.extend(loud);
+        }
+        assert_eq!(samples, expected);
+    }
+
+    #[test]
+    fn test_clipped_samples_are_counted() {
+        let test_path = "test_clipped_samples.wav";
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        let warnings = capture_warnings(|| {
+            recorder.write_audio_chunk(&[0.5, 1.0, -1.0]).expect("Failed to write chunk");
+            assert_eq!(recorder.clipped_sample_count(), 0);
+            recorder.write_audio_chunk(&[1.5, -2.0, 0.1]).expect("Failed to write chunk");
+        });
+        assert_eq!(recorder.clipped_sample_count(), 2);
+        assert_eq!(warnings.iter().filter(|w| w.contains("clipped")).count(), 1, "{:?}", warnings);
+
+        recorder.set_channel_gains(&[4.0]).expect("Failed to set gains");
+        recorder.write_audio_chunk_i16(&[100, 16000]).expect("Failed to write chunk");
+        assert_eq!(recorder.clipped_sample_count(), 3);
+        recorder.finalize().expect("Failed to finalize");
+        let _ = fs::remove_file(test_path);
+
+        // Float recordings keep out-of-range samples.
+        let test_path = "test_clipped_samples_float.wav";
+        let mut recorder = WavAudioRecorder::new_with_format(Some(test_path), SampleFormat::Float, 32).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[1.5, -2.0]).expect("Failed to write chunk");
+        assert_eq!(recorder.clipped_sample_count(), 0);
+        recorder.finalize().expect("Failed to finalize");
+        let _ = fs::remove_file(test_path);
+    }
+
+    /// Interleaves `left` and `right` into one stereo buffer.
+    fn interleave(left: &[f32], right: &[f32]) -> Vec<f32> {
+        left.iter().zip(right).flat_map(|(&l, &r)| [l, r]).collect()
+    }
+
+    #[test]
+    fn test_looks_like_interleaved_stereo() {
+        let tone = sine(440.0, 16000, 0.1);
+        assert!(!looks_like_interleaved_stereo(&tone));
+        assert!(!looks_like_interleaved_stereo(&vec![0.0; 4096]));
+        // A mono microphone exposed as a stereo device: nearly identical channels.
+        let right: Vec<f32> = tone.iter().map(|s| s * 0.98).collect();
+        assert!(looks_like_interleaved_stereo(&interleave(&tone, &right)));
+        // Too short to judge.
+        assert!(!looks_like_interleaved_stereo(&interleave(&tone[..100], &tone[..100])));
+    }
+
+    /// Logger that records warnings per thread, so tests running in parallel see only their own.
+    struct CapturingLogger;
+
+    thread_local! {
+        static CAPTURED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                CAPTURED.with(|captured| captured.borrow_mut().push(record.args().to_string()));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Runs `f` and returns the warnings it logged.
+    fn capture_warnings(f: impl FnOnce()) -> Vec<String> {
+        static LOGGER: CapturingLogger = CapturingLogger;
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            if log::set_logger(&LOGGER).is_ok() {
+                log::set_max_level(log::LevelFilter::Warn);
+            }
+        });
+        CAPTURED.with(|captured| captured.borrow_mut().clear());
+        f();
+        CAPTURED.with(|captured| captured.take())
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_mono_recorder_warns_once_about_interleaved_chunks() {
+        let test_path = "test_interleaved_warning.wav";
+        let tone = sine(440.0, 16000, 0.1);
+        let warnings = capture_warnings(|| {
+            let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+            recorder.write_audio_chunk(&tone).expect("Failed to write chunk");
+            let stereo = interleave(&tone, &tone);
+            recorder.write_audio_chunk(&stereo).expect("Failed to write chunk");
+            recorder.write_audio_chunk(&stereo).expect("Failed to write chunk");
+            recorder.finalize().expect("Failed to finalize");
+        });
+        let interleaved: Vec<&String> = warnings.iter().filter(|w| w.contains("interleaved stereo")).collect();
+        assert_eq!(interleaved.len(), 1, "{:?}", warnings);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_write_interleaved_chunk_rejects_channel_mismatch() {
+        let test_path = "test_write_interleaved_chunk.wav";
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        assert!(matches!(recorder.write_interleaved_chunk(&[0.1, 0.1], 2), Err(WhisperStreamError::WavWrite(_))));
+        recorder.write_interleaved_chunk(&[0.1, 0.2], 1).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let spec = WavSpec { channels: 2, sample_rate: 48000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        let mut recorder = WavAudioRecorder::with_spec(Some(test_path), spec).expect("Failed to create recorder");
+        assert!(matches!(recorder.write_interleaved_chunk(&[0.1, 0.2, 0.3], 2), Err(WhisperStreamError::WavWrite(_))));
+        recorder.write_interleaved_chunk(&[0.1, 0.2], 2).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+        let _ = fs::remove_file(test_path);
+    }
+
+    fn sine(freq: f32, rate: u32, secs: f32) -> Vec<f32> {
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_resample_to_16k_length_matches_ratio() {
+        for rate in [8000, 22050, 44100, 48000] {
+            let input = sine(440.0, rate, 1.5);
+            let output = resample_to_16k(&input, rate).expect("Failed to resample");
+            let expected = (input.len() as u64 * 16000).div_ceil(rate as u64) as usize;
+            assert_eq!(output.len(), expected, "rate {}", rate);
+            // A tone well below 8kHz keeps its level (0.5 / sqrt(2)), apart from the edges.
+            let middle = &output[1600..output.len() - 1600];
+            assert!((rms(middle) - 0.3536).abs() < 0.01, "rate {}: rms {}", rate, rms(middle));
+        }
+    }
+
+    #[test]
+    fn test_resample_to_16k_filters_content_above_nyquist() {
+        let input = sine(12000.0, 48000, 1.0);
+        let output = resample_to_16k(&input, 48000).expect("Failed to resample");
+        assert!(rms(&output) < 0.01, "aliased energy: rms {}", rms(&output));
+    }
+
+    #[test]
+    fn test_resample_to_16k_borrows_16k_input() {
+        let input = sine(440.0, 16000, 0.1);
+        let output = resample_to_16k(&input, 16000).expect("Failed to resample");
+        assert!(matches!(output, Cow::Borrowed(_)));
+        assert!(resample_to_16k(&input, 0).is_err());
+    }
+
+    fn write_test_wav<S: hound::Sample + Copy>(path: &str, spec: WavSpec, samples: &[S]) {
+        let mut writer = WavWriter::create(path, spec).expect("Failed to create WAV");
+        for &sample in samples {
+            writer.write_sample(sample).expect("Failed to write sample");
+        }
+        writer.finalize().expect("Failed to finalize WAV");
+    }
+
+    #[test]
+    fn test_read_wav_as_f32_downmixes_and_reports_rate() {
+        let test_path = "test_read_wav_as_f32_stereo.wav";
+        let spec = WavSpec { channels: 2, sample_rate: 44100, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        write_test_wav(test_path, spec, &[16384i16, 0, -32768, -32768]);
+
+        let audio = read_wav_as_f32(test_path).expect("Failed to read WAV");
+        assert_eq!(audio.sample_rate, 44100);
+        assert_eq!(audio.samples, vec![0.25, -1.0]);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_read_wav_as_f32_reads_float_and_24_bit() {
+        let float_path = "test_read_wav_as_f32_float.wav";
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 32, sample_format: SampleFormat::Float };
+        write_test_wav(float_path, spec, &[0.5f32, -0.125, 2.0]);
+        assert_eq!(read_wav_as_f32(float_path).unwrap().samples, vec![0.5, -0.125, 1.0]);
+        let _ = fs::remove_file(float_path);
+
+        let int24_path = "test_read_wav_as_f32_24bit.wav";
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 24, sample_format: SampleFormat::Int };
+        write_test_wav(int24_path, spec, &[1i32 << 22, -(1 << 23)]);
+        assert_eq!(read_wav_as_f32(int24_path).unwrap().samples, vec![0.5, -1.0]);
+        let _ = fs::remove_file(int24_path);
+    }
+
+    #[test]
+    fn test_read_wav_as_f32_downmixes_surround() {
+        let test_path = "test_read_wav_as_f32_surround.wav";
+        let spec = WavSpec { channels: 4, sample_rate: 48000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        write_test_wav(test_path, spec, &[16384i16, 16384, 0, 0, -8192, -8192, -8192, -8192]);
+        assert_eq!(read_wav_as_f32(test_path).unwrap().samples, vec![0.25, -0.25]);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_trim_silence() {
+        let samples = [0.0, 0.01, 0.0, 0.5, -0.2, 0.0, -0.6, 0.02, 0.0, 0.0];
+        assert_eq!(trim_silence(&samples, 0.1, 0), &[0.5, -0.2, 0.0, -0.6]);
+        assert_eq!(trim_silence(&samples, 0.1, 2), &[0.01, 0.0, 0.5, -0.2, 0.0, -0.6, 0.02, 0.0]);
+        // The margin is clamped to the input.
+        assert_eq!(trim_silence(&samples, 0.1, 100), &samples);
+    }
+
+    #[test]
+    fn test_trim_silence_edge_cases() {
+        assert!(trim_silence(&[0.0, 0.05, -0.05, 0.0], 0.1, 3).is_empty());
+        assert!(trim_silence(&[], 0.1, 3).is_empty());
+        let loud = [0.5, -0.5, 0.9, -0.3];
+        assert_eq!(trim_silence(&loud, 0.1, 0), &loud);
+    }
+
+    #[test]
+    fn test_downmix_to_mono() {
+        assert_eq!(downmix_to_mono(&[0.5, 0.25, -1.0, 0.0, 0.2, 0.2], 2), vec![0.375, -0.5, 0.2]);
+        assert_eq!(downmix_to_mono(&[0.25, 0.5, 0.75, 0.0, 0.0, 0.75], 3), vec![0.5, 0.25]);
+        assert_eq!(downmix_to_mono(&[0.1, -0.2, 0.3], 1), vec![0.1, -0.2, 0.3]);
+        // A trailing partial frame is dropped.
+        assert_eq!(downmix_to_mono(&[1.0, 0.0, 0.5], 2), vec![0.5]);
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_duration_secs() {
+        let test_path = "test_wav_recorder_duration.wav";
+        let _ = fs::remove_file(test_path);
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.1; 8000]).expect("Failed to write chunk");
+        recorder.write_audio_chunk(&[0.1; 16000]).expect("Failed to write chunk");
+        assert_eq!(recorder.duration_secs(), 1.5);
+        let message = recorder.finalize().expect("Failed to finalize").unwrap();
+        assert!(message.contains("1.50s"), "{}", message);
+        let _ = fs::remove_file(test_path);
+
+        let spec = WavSpec { channels: 2, sample_rate: 48000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        let stereo_path = "test_wav_recorder_duration_stereo.wav";
+        let mut recorder = WavAudioRecorder::with_spec(Some(stereo_path), spec).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.1; 48000]).expect("Failed to write chunk");
+        assert_eq!(recorder.duration_secs(), 0.5);
+        recorder.finalize().expect("Failed to finalize");
+        let _ = fs::remove_file(stereo_path);
+
+        assert_eq!(WavAudioRecorder::new(None).unwrap().duration_secs(), 0.0);
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_float_format_is_lossless() {
+        let test_path = "test_wav_recorder_float.wav";
+        let _ = fs::remove_file(test_path);
+        let samples = [0.123_456_79f32, -0.987_654_3, 0.5, 1e-7];
+        let mut recorder = WavAudioRecorder::new_with_format(Some(test_path), SampleFormat::Float, 32)
+            .expect("Failed to create recorder");
+        recorder.write_audio_chunk(&samples).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let mut reader = hound::WavReader::open(test_path).expect("Failed to open WAV");
+        assert_eq!(reader.spec().sample_format, SampleFormat::Float);
+        assert_eq!(reader.spec().bits_per_sample, 32);
+        let read: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(read, samples);
+        let _ = fs::remove_file(test_path);
+
+        assert!(WavAudioRecorder::new_with_format(None, SampleFormat::Float, 16).is_err());
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_with_spec_writes_header() {
+        let test_path = "test_wav_recorder_48k_stereo.wav";
+        let _ = fs::remove_file(test_path);
+        let spec = WavSpec { channels: 2, sample_rate: 48000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+        let mut recorder = WavAudioRecorder::with_spec(Some(test_path), spec).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.5, -0.5, 0.25, -0.25]).expect("Failed to write chunk");
+        recorder.finalize().expect("Failed to finalize");
+
+        let reader = hound::WavReader::open(test_path).expect("Failed to open WAV");
+        assert_eq!(reader.spec(), spec);
+        assert_eq!(reader.duration(), 2);
+        let _ = fs::remove_file(test_path);
+
+        assert!(WavAudioRecorder::with_spec(None, WavSpec { channels: 0, ..spec }).is_err());
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_append_continues_recording() {
+        let test_path = "test_wav_recorder_append.wav";
+        let _ = fs::remove_file(test_path);
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+
+        let mut recorder = WavAudioRecorder::open(Some(test_path), spec, true).expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.1, 0.2, 0.3]).expect("Failed to write chunk");
+        drop(recorder);
+
+        let mut recorder = WavAudioRecorder::open(Some(test_path), spec, true).expect("Failed to reopen recorder");
+        recorder.write_audio_chunk(&[0.4, 0.5]).expect("Failed to write chunk");
+        assert_eq!(recorder.duration_secs(), 5.0 / 16000.0);
+        recorder.finalize().expect("Failed to finalize");
+        assert_eq!(hound::WavReader::open(test_path).unwrap().duration(), 5);
+
+        let stereo = WavSpec { channels: 2, ..spec };
+        match WavAudioRecorder::open(Some(test_path), stereo, true) {
+            Err(WhisperStreamError::WavWrite(message)) => assert!(message.contains("Cannot append"), "{}", message),
+            other => panic!("expected a spec mismatch error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(hound::WavReader::open(test_path).unwrap().duration(), 5);
+        let _ = fs::remove_file(test_path);
+    }
+
+    #[test]
+    fn test_ring_buffer_recorder_keeps_newest_samples() {
+        let mut recorder = RingBufferRecorder::new(4);
+        assert!(recorder.is_empty());
+        recorder.write_audio_chunk(&[1.0, 2.0, 3.0]);
+        assert_eq!(recorder.snapshot(), vec![1.0, 2.0, 3.0]);
+        recorder.write_audio_chunk(&[4.0, 5.0]);
+        assert_eq!(recorder.snapshot(), vec![2.0, 3.0, 4.0, 5.0]);
+        recorder.write_audio_chunk(&[6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+        assert_eq!(recorder.snapshot(), vec![8.0, 9.0, 10.0, 11.0]);
+        assert_eq!(recorder.len(), 4);
+        recorder.clear();
+        assert!(recorder.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_rotating_wav_recorder_rotates_on_limits() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-rotating");
+        let _ = fs::remove_dir_all(&dir);
+        let spec = WavSpec { channels: 1, sample_rate: 16000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+
+        // 1ms at 16kHz is 16 samples.
+        let mut recorder = RotatingWavRecorder::new(&dir, "capture", spec, Some(Duration::from_millis(1)), None)
+            .expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.1; 16]).expect("Failed to write chunk");
+        assert_eq!(recorder.finished_paths(), [dir.join("capture-0001.wav")]);
+        recorder.write_audio_chunk(&[0.1; 10]).expect("Failed to write chunk");
+        recorder.write_audio_chunk(&[0.1; 10]).expect("Failed to write chunk");
+        recorder.write_audio_chunk(&[0.1; 4]).expect("Failed to write chunk");
+        let paths = recorder.finalize().expect("Failed to finalize");
+        assert_eq!(paths, [dir.join("capture-0001.wav"), dir.join("capture-0002.wav"), dir.join("capture-0003.wav")]);
+        let durations: Vec<u32> = paths.iter().map(|p| hound::WavReader::open(p).unwrap().duration()).collect();
+        assert_eq!(durations, [16, 20, 4]);
+
+        // A 40-byte limit is 20 16-bit samples.
+        let mut recorder = RotatingWavRecorder::new(&dir, "bytes", spec, None, Some(40))
+            .expect("Failed to create recorder");
+        recorder.write_audio_chunk(&[0.1; 25]).expect("Failed to write chunk");
+        let paths = recorder.finalize().expect("Failed to finalize");
+        assert_eq!(paths, [dir.join("bytes-0001.wav")]);
+        assert!(!dir.join("bytes-0002.wav").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_wav_audio_recorder_channel_gains() {
+        let test_path = "test_channel_gains.wav";
+        let _ = fs::remove_file(test_path);
+        let mut recorder = WavAudioRecorder::new(Some(test_path)).expect("Failed to create recorder");
+        assert!(recorder.set_channel_gains(&[1.0, 1.0]).is_err());
+        recorder.set_channel_gains(&[0.5]).expect("Failed to set gains");
+        recorder.write_audio_chunk(&[0.5, -0.5]).expect("Failed to write chunk");
+        recorder.set_channel_gains(&[100.0]).expect("Failed to set gains");
+        assert_eq!(recorder.channel_gains, vec![WavAudioRecorder::MAX_CHANNEL_GAIN]);
+        recorder.finalize().expect("Failed to finalize");
+
+        let (samples, _) = read_wav_i16(test_path).expect("Failed to read WAV");
+        assert_eq!(samples, vec![8192, -8192]);
+        let _ = fs::remove_file(test_path);
+    }
+
     #[test]
     fn test_wav_audio_recorder_no_path() {
         let recorder = WavAudioRecorder::new(None).expect("Failed to create recorder");
@@ -0,0 +1,54 @@
+//! Cooperative cancellation of in-flight transcriptions.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use whisper_rs::FullParams;
+
+/// A cheaply clonable flag that aborts transcriptions it is attached to.
+///
+/// Clones share the same flag, so hand one clone to the transcription and keep another to
+/// call [`CancellationToken::cancel`] from any thread, e.g. when a client disconnects.
+/// Whisper polls the flag during inference, so a running decode stops promptly and the call
+/// returns [`crate::WhisperStreamError::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation. Cannot be undone; create a new token for the next request.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether [`CancellationToken::cancel`] was called on this token or a clone.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Makes Whisper abort a decode with `params` once this token is cancelled.
+    pub(crate) fn attach(&self, params: &mut FullParams) {
+        let token = self.clone();
+        params.set_abort_callback_safe(move || token.is_cancelled());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let handle = token.clone();
+        assert!(!token.is_cancelled());
+        handle.cancel();
+        assert!(token.is_cancelled());
+        assert!(!CancellationToken::new().is_cancelled());
+    }
+}
@@ -1,4 +1,5 @@
 use thiserror::Error;
+#[cfg(feature = "microphone")]
 use cpal::{BuildStreamError, DefaultStreamConfigError, DevicesError, StreamError as CpalStreamError};
 
 /// Represents all possible errors that can occur within the `whisper-stream-rs` library.
@@ -28,6 +29,9 @@ pub enum WhisperStreamError {
     #[error("Failed to create or manage Whisper context/state: {0}")]
     Context(String),
 
+    #[error("Failed to initialize Whisper context: {0}")]
+    ContextInit(String),
+
     #[error("Error during audio transcription: {0}")]
     Transcription(String),
 
@@ -40,6 +44,30 @@ pub enum WhisperStreamError {
     #[error("Failed to retrieve or download model: {0}")]
     ModelFetch(String),
 
+    #[error("Model {model} is not cached at {} and downloads are disabled (offline mode)", path.display())]
+    ModelNotCached { model: String, path: std::path::PathBuf },
+
+    #[error("Cache directory {} is not writable ({source}); set WHISPER_STREAM_CACHE to a writable directory", path.display())]
+    CacheDirNotWritable { path: std::path::PathBuf, source: std::io::Error },
+
+    #[error("Not enough disk space for the download: {needed} bytes needed, {available} bytes available")]
+    InsufficientSpace { needed: u64, available: u64 },
+
+    #[error("Checksum mismatch: expected SHA-256 {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Out of memory while loading the Whisper model: {0}")]
+    OutOfMemory(String),
+
+    #[error("Failed to decode audio file: {0}")]
+    Decode(String),
+
+    #[error("Unsupported audio format: {details}")]
+    UnsupportedAudioFormat { details: String },
+
+    #[error("Operation was cancelled")]
+    Cancelled,
+
     #[error("I/O error: {source}")]
     Io { #[from] source: std::io::Error },
 
@@ -49,15 +77,19 @@ pub enum WhisperStreamError {
     #[error("Whisper context error: {source}")]
     WhisperContext { #[from] source: whisper_rs::WhisperError },
 
+    #[cfg(feature = "microphone")]
     #[error("CPAL device enumeration error: {source}")]
     CpalDevicesError { #[from] source: DevicesError },
 
+    #[cfg(feature = "microphone")]
     #[error("CPAL default stream config error: {source}")]
     CpalDefaultConfigError { #[from] source: DefaultStreamConfigError },
 
+    #[cfg(feature = "microphone")]
     #[error("CPAL build stream error: {source}")]
     CpalBuildStreamError { #[from] source: BuildStreamError },
 
+    #[cfg(feature = "microphone")]
     #[error("CPAL runtime stream error: {0}")]
     CpalRuntimeStreamError(String),
 
@@ -66,6 +98,7 @@ pub enum WhisperStreamError {
 }
 
 // Manual conversion for CpalStreamError as it's an enum and needs specific handling
+#[cfg(feature = "microphone")]
 impl From<CpalStreamError> for WhisperStreamError {
     fn from(err: CpalStreamError) -> Self {
         WhisperStreamError::CpalRuntimeStreamError(err.to_string())
@@ -2,14 +2,55 @@
 //!
 //! A library for performing real-time transcription using Whisper ASR models.
 //! It handles audio capture, processing, and streaming results.
+//!
+//! Microphone capture ([`WhisperStream`], [`AudioInput`]) needs the default `microphone`
+//! feature. Without it, e.g. for headless builds without ALSA, transcription of files and
+//! buffers and model management are still available.
 
+#[cfg(feature = "microphone")]
 mod audio;
 mod model;
 mod error;
 mod audio_utils;
+mod cancel;
+#[cfg(feature = "microphone")]
 mod score;
+mod transcript;
+mod transcribe;
+mod streaming;
+#[cfg(feature = "microphone")]
 mod whisper_stream;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+#[cfg(feature = "spectrogram")]
+mod spectrogram;
+#[cfg(feature = "serde")]
+mod transcription_cache;
+#[cfg(feature = "compressed-audio")]
+mod audio_decode;
 // New public API
+#[cfg(feature = "microphone")]
 pub use whisper_stream::{WhisperStream, Event};
 pub use error::WhisperStreamError;
-pub use model::Model;
+pub use cancel::CancellationToken;
+#[cfg(feature = "microphone")]
+pub use audio::{AudioInput, list_input_devices};
+pub use hound::{SampleFormat, WavSpec};
+pub use audio_utils::{Agc, PadMode, RingBufferRecorder, RotatingWavRecorder, SilenceDetector, SilenceGate, Vad, WavAudio, WavAudioRecorder, WavAudioRecorderBuilder, apply_fade, apply_gain, coalesce_segments, concat_segments, downmix_to_mono, is_speech, looks_like_interleaved_stereo, measure_lufs, normalize_peak, pad_audio_edge, pad_audio_with_mode, pad_audio_with_value, read_wav_as_f32, read_wav_i16, resample_to_16k, trim_silence};
+pub use transcript::{Segment, TranscriptionResult, WordTiming, TimestampFormat, split_into_sentences, to_srt, to_vtt, words_per_minute, write_timestamped_text};
+pub use streaming::StreamingTranscriber;
+pub use transcribe::{transcribe, transcribe_cancellable, transcribe_chunked, transcribe_long, transcribe_url, transcribe_url_with_options, transcribe_words, Task, TranscribeChunks, TranscribeOptions, DecodingParams};
+#[cfg(feature = "spectrogram")]
+pub use spectrogram::save_spectrogram;
+#[cfg(feature = "serde")]
+pub use transcript::to_json;
+#[cfg(feature = "serde")]
+pub use transcription_cache::{set_transcription_cache_enabled, transcription_cache_enabled, clear_transcription_cache};
+#[cfg(feature = "async")]
+pub use model::ensure_model_async;
+#[cfg(feature = "compressed-audio")]
+pub use audio_decode::decode_audio_file;
+pub use model::{
+    Model, CacheLayout, CacheValidators, DownloadOptions, Downloader, Freshness, HttpDownloader, ProgressCallback, ExtractProgressCallback, ensure_model, ensure_models, ensure_models_with_options, ensure_model_with_options,
+    ensure_model_with_progress, ensure_model_with_mirror, ensure_model_from_path, ensure_model_in, prefetch_all, prefetch_all_with_options, PrefetchReport, CACHE_DIR_ENV, MODEL_MIRROR_ENV, DEFAULT_MODEL_BASE_URL, verify_cache_entry, model_identity, load_whisper_context, clear_model_cache, remove_model, list_cached_models, ensure_model_check_only, is_model_cached, set_offline, is_offline, CachedModel, set_default_model, get_default_model, default_model,
+};
@@ -1,17 +1,21 @@
 use std::path::{PathBuf, Path};
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::cancel::CancellationToken;
 use crate::error::WhisperStreamError;
-use log::{info};
+use log::{info, warn};
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use whisper_rs::{WhisperContext, WhisperContextParameters};
 
-#[cfg(feature = "coreml")]
+#[cfg(any(feature = "coreml", test))]
 use zip::ZipArchive;
-#[cfg(feature = "coreml")]
+#[cfg(any(feature = "coreml", test))]
 use std::fs::File;
-#[cfg(feature = "coreml")]
-use log::{warn};
 
 /// Supported Whisper models.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +26,34 @@ pub enum Model {
     TinyEn,
     /// The small.en model
     SmallEn,
+    /// The medium.en model (~1.5GB)
+    MediumEn,
+    /// The multilingual tiny model
+    Tiny,
+    /// The multilingual base model
+    Base,
+    /// The multilingual small model
+    Small,
+    /// The multilingual large-v3 model (~3.1GB)
+    LargeV3,
+    /// tiny.en-q5_1, quantized (q5_1)
+    TinyEnQ5_1,
+    /// tiny.en-q8_0, quantized (q8_0)
+    TinyEnQ8_0,
+    /// base.en-q5_1, quantized (q5_1)
+    BaseEnQ5_1,
+    /// base.en-q8_0, quantized (q8_0)
+    BaseEnQ8_0,
+    /// small.en-q5_1, quantized (q5_1)
+    SmallEnQ5_1,
+    /// small.en-q8_0, quantized (q8_0)
+    SmallEnQ8_0,
+    /// medium.en-q5_0, quantized (q5_0)
+    MediumEnQ5_0,
+    /// medium.en-q8_0, quantized (q8_0)
+    MediumEnQ8_0,
+    /// large-v3-q5_0, quantized (q5_0)
+    LargeV3Q5_0,
 }
 
 impl Model {
@@ -31,6 +63,20 @@ impl Model {
             Model::BaseEn => "base.en",
             Model::TinyEn => "tiny.en",
             Model::SmallEn => "small.en",
+            Model::MediumEn => "medium.en",
+            Model::Tiny => "tiny",
+            Model::Base => "base",
+            Model::Small => "small",
+            Model::LargeV3 => "large-v3",
+            Model::TinyEnQ5_1 => "tiny.en-q5_1",
+            Model::TinyEnQ8_0 => "tiny.en-q8_0",
+            Model::BaseEnQ5_1 => "base.en-q5_1",
+            Model::BaseEnQ8_0 => "base.en-q8_0",
+            Model::SmallEnQ5_1 => "small.en-q5_1",
+            Model::SmallEnQ8_0 => "small.en-q8_0",
+            Model::MediumEnQ5_0 => "medium.en-q5_0",
+            Model::MediumEnQ8_0 => "medium.en-q8_0",
+            Model::LargeV3Q5_0 => "large-v3-q5_0",
         }
     }
     /// Returns the model file name (e.g., "ggml-base.en.bin").
@@ -39,19 +85,155 @@ impl Model {
             Model::BaseEn => "ggml-base.en.bin",
             Model::TinyEn => "ggml-tiny.en.bin",
             Model::SmallEn => "ggml-small.en.bin",
+            Model::MediumEn => "ggml-medium.en.bin",
+            Model::Tiny => "ggml-tiny.bin",
+            Model::Base => "ggml-base.bin",
+            Model::Small => "ggml-small.bin",
+            Model::LargeV3 => "ggml-large-v3.bin",
+            Model::TinyEnQ5_1 => "ggml-tiny.en-q5_1.bin",
+            Model::TinyEnQ8_0 => "ggml-tiny.en-q8_0.bin",
+            Model::BaseEnQ5_1 => "ggml-base.en-q5_1.bin",
+            Model::BaseEnQ8_0 => "ggml-base.en-q8_0.bin",
+            Model::SmallEnQ5_1 => "ggml-small.en-q5_1.bin",
+            Model::SmallEnQ8_0 => "ggml-small.en-q8_0.bin",
+            Model::MediumEnQ5_0 => "ggml-medium.en-q5_0.bin",
+            Model::MediumEnQ8_0 => "ggml-medium.en-q8_0.bin",
+            Model::LargeV3Q5_0 => "ggml-large-v3-q5_0.bin",
         }
     }
+    /// Returns the model whose file name is `file_name` (e.g. `"ggml-base.en.bin"`), if any.
+    ///
+    /// This is the inverse of [`Model::file_name`]; use [`FromStr`] to parse names like `"base.en"`.
+    pub fn from_file_name(file_name: &str) -> Option<Model> {
+        Model::list().into_iter().find(|model| model.file_name() == file_name)
+    }
     /// Returns the model download URL.
     pub fn url(&self) -> &'static str {
         match self {
             Model::BaseEn => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin",
             Model::TinyEn => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin",
             Model::SmallEn => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin",
+            Model::MediumEn => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en.bin",
+            Model::Tiny => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.bin",
+            Model::Base => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.bin",
+            Model::Small => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.bin",
+            Model::LargeV3 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin",
+            Model::TinyEnQ5_1 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en-q5_1.bin",
+            Model::TinyEnQ8_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en-q8_0.bin",
+            Model::BaseEnQ5_1 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en-q5_1.bin",
+            Model::BaseEnQ8_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en-q8_0.bin",
+            Model::SmallEnQ5_1 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en-q5_1.bin",
+            Model::SmallEnQ8_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en-q8_0.bin",
+            Model::MediumEnQ5_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en-q5_0.bin",
+            Model::MediumEnQ8_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en-q8_0.bin",
+            Model::LargeV3Q5_0 => "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-q5_0.bin",
+        }
+    }
+    /// Returns the download URL of this model on a mirror serving files from `base_url`.
+    ///
+    /// The mirror must lay files out like Hugging Face: `<base_url>/<file_name>`.
+    pub fn url_with_base(&self, base_url: &str) -> String {
+        format!("{}/{}", base_url.trim_end_matches('/'), self.file_name())
+    }
+    /// Returns `true` if the model transcribes languages other than English.
+    ///
+    /// English-only (`.en`) models ignore the language setting, so only offer a language
+    /// choice for multilingual ones.
+    pub fn is_multilingual(&self) -> bool {
+        match self {
+            Model::BaseEn | Model::TinyEn | Model::SmallEn | Model::MediumEn
+            | Model::TinyEnQ5_1 | Model::TinyEnQ8_0 | Model::BaseEnQ5_1 | Model::BaseEnQ8_0
+            | Model::SmallEnQ5_1 | Model::SmallEnQ8_0 | Model::MediumEnQ5_0 | Model::MediumEnQ8_0 => false,
+            Model::Tiny | Model::Base | Model::Small | Model::LargeV3 | Model::LargeV3Q5_0 => true,
+        }
+    }
+    /// Returns the SHA-256 digest pinned for this model's file, if one is known.
+    ///
+    /// No model has a pinned digest yet, so this currently always returns `None` and downloads
+    /// are not checked against upstream: the digest of whatever is first downloaded is recorded
+    /// in the cache and trusted from then on. That still detects a cached file that is later
+    /// truncated or corrupted, but not one that was served wrong in the first place. Once a
+    /// digest is pinned, downloads not matching it fail with
+    /// [`WhisperStreamError::ChecksumMismatch`].
+    pub fn expected_sha256(&self) -> Option<&'static str> {
+        PINNED_SHA256.iter().find(|(model, _)| model == self).map(|(_, digest)| *digest)
+    }
+    /// Returns the quantization type (e.g. `"q5_1"`) of quantized models, or `None` for full precision.
+    ///
+    /// Quantized models are several times smaller to download and faster to run, at a small
+    /// cost in accuracy.
+    pub fn quantization(&self) -> Option<&'static str> {
+        self.name().rsplit_once('-').map(|(_, q)| q).filter(|q| q.starts_with('q'))
+    }
+    /// Returns the approximate size of the model file download in bytes.
+    pub fn download_size_bytes(&self) -> u64 {
+        match self {
+            Model::BaseEn => 147_964_211,
+            Model::TinyEn => 77_704_715,
+            Model::SmallEn => 487_614_201,
+            Model::MediumEn => 1_533_774_781,
+            Model::Tiny => 77_691_713,
+            Model::Base => 147_951_465,
+            Model::Small => 487_601_967,
+            Model::LargeV3 => 3_095_033_483,
+            Model::TinyEnQ5_1 => 32_166_155,
+            Model::TinyEnQ8_0 => 43_550_795,
+            Model::BaseEnQ5_1 => 59_721_011,
+            Model::BaseEnQ8_0 => 81_781_811,
+            Model::SmallEnQ5_1 => 190_098_681,
+            Model::SmallEnQ8_0 => 264_477_561,
+            Model::MediumEnQ5_0 => 539_212_467,
+            Model::MediumEnQ8_0 => 823_369_779,
+            Model::LargeV3Q5_0 => 1_081_140_203,
+        }
+    }
+    /// Returns the number of parameters of the model architecture.
+    ///
+    /// Quantization shrinks the parameters but doesn't change how many there are.
+    pub fn parameter_count(&self) -> u64 {
+        match self {
+            Model::TinyEn | Model::Tiny | Model::TinyEnQ5_1 | Model::TinyEnQ8_0 => 39_000_000,
+            Model::BaseEn | Model::Base | Model::BaseEnQ5_1 | Model::BaseEnQ8_0 => 74_000_000,
+            Model::SmallEn | Model::Small | Model::SmallEnQ5_1 | Model::SmallEnQ8_0 => 244_000_000,
+            Model::MediumEn | Model::MediumEnQ5_0 | Model::MediumEnQ8_0 => 769_000_000,
+            Model::LargeV3 | Model::LargeV3Q5_0 => 1_550_000_000,
+        }
+    }
+    /// Returns the approximate memory needed to run the model, in megabytes.
+    ///
+    /// Full-precision figures are whisper.cpp's published numbers; quantized models are
+    /// estimated as their file size plus the compute buffers of the full-precision model.
+    pub fn approx_ram_mb(&self) -> u32 {
+        match self {
+            Model::TinyEn | Model::Tiny => 273,
+            Model::BaseEn | Model::Base => 388,
+            Model::SmallEn | Model::Small => 852,
+            Model::MediumEn => 2100,
+            Model::LargeV3 => 3900,
+            Model::TinyEnQ5_1 => 230,
+            Model::TinyEnQ8_0 => 240,
+            Model::BaseEnQ5_1 => 305,
+            Model::BaseEnQ8_0 => 325,
+            Model::SmallEnQ5_1 => 570,
+            Model::SmallEnQ8_0 => 640,
+            Model::MediumEnQ5_0 => 1115,
+            Model::MediumEnQ8_0 => 1385,
+            Model::LargeV3Q5_0 => 2030,
         }
     }
     /// Returns all supported models.
     pub fn list() -> Vec<Model> {
-        vec![Model::BaseEn, Model::TinyEn, Model::SmallEn]
+        vec![
+            Model::BaseEn, Model::TinyEn, Model::SmallEn, Model::MediumEn,
+            Model::Tiny, Model::Base, Model::Small, Model::LargeV3,
+            Model::TinyEnQ5_1, Model::TinyEnQ8_0, Model::BaseEnQ5_1, Model::BaseEnQ8_0,
+            Model::SmallEnQ5_1, Model::SmallEnQ8_0, Model::MediumEnQ5_0, Model::MediumEnQ8_0,
+            Model::LargeV3Q5_0,
+        ]
+    }
+    /// Returns all supported models; the same as [`Model::list`].
+    pub fn all() -> Vec<Model> {
+        Model::list()
     }
 }
 
@@ -68,6 +250,20 @@ impl FromStr for Model {
             "base.en" => Ok(Model::BaseEn),
             "tiny.en" => Ok(Model::TinyEn),
             "small.en" => Ok(Model::SmallEn),
+            "medium.en" => Ok(Model::MediumEn),
+            "tiny" => Ok(Model::Tiny),
+            "base" => Ok(Model::Base),
+            "small" => Ok(Model::Small),
+            "large-v3" => Ok(Model::LargeV3),
+            "tiny.en-q5_1" => Ok(Model::TinyEnQ5_1),
+            "tiny.en-q8_0" => Ok(Model::TinyEnQ8_0),
+            "base.en-q5_1" => Ok(Model::BaseEnQ5_1),
+            "base.en-q8_0" => Ok(Model::BaseEnQ8_0),
+            "small.en-q5_1" => Ok(Model::SmallEnQ5_1),
+            "small.en-q8_0" => Ok(Model::SmallEnQ8_0),
+            "medium.en-q5_0" => Ok(Model::MediumEnQ5_0),
+            "medium.en-q8_0" => Ok(Model::MediumEnQ8_0),
+            "large-v3-q5_0" => Ok(Model::LargeV3Q5_0),
             _ => Err(()),
         }
     }
@@ -76,116 +272,2718 @@ impl FromStr for Model {
 
 #[cfg(feature = "coreml")]
 const COREML_MODEL_URL_TEMPLATE: &str = "https://models.milan.place/whisper-cpp/metal//{}-encoder.mlmodelc.zip";
+/// Base names of the models with a CoreML encoder published under [`COREML_MODEL_URL_TEMPLATE`].
 #[cfg(feature = "coreml")]
-const BASE_MODEL_NAME_FOR_COREML: &str = "ggml-base.en"; // Corresponds to ggml-base.en.bin
+const COREML_ENCODER_BASE_NAMES: &[&str] = &[
+    "ggml-tiny", "ggml-tiny.en", "ggml-base", "ggml-base.en",
+    "ggml-small", "ggml-small.en", "ggml-medium.en", "ggml-large-v3",
+];
+
+/// Returns the name whisper.cpp derives a CoreML encoder path from for the model file stem `stem`.
+///
+/// Like whisper.cpp, this strips a quantization suffix, so `ggml-base.en-q5_1` uses the
+/// `ggml-base.en-encoder.mlmodelc` encoder of its full-precision model.
+fn coreml_base_name(stem: &str) -> &str {
+    match stem.rsplit_once('-') {
+        Some((base, suffix)) if suffix.starts_with('q') && suffix[1..].contains('_') => base,
+        _ => stem,
+    }
+}
+
+/// SHA-256 digests of upstream model files, as published by the model host.
+///
+/// Empty until the digests are taken from the upstream files; models without an entry are
+/// checked against the digest recorded when they were downloaded.
+const PINNED_SHA256: &[(Model, &str)] = &[];
+
+/// Extension of the file next to a cached model that records its SHA-256 digest.
+const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// Name of the cache subdirectory holding content-addressed model files.
+const BY_HASH_DIR_NAME: &str = "by-hash";
+
+/// How downloaded models are laid out inside the cache directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheLayout {
+    /// Models are stored directly under their `file_name()` (the default).
+    #[default]
+    Flat,
+    /// Models are stored as `by-hash/<sha256>` and `file_name()` is a hard link to that entry.
+    ///
+    /// Identical files downloaded under different names share one copy on disk, and an entry
+    /// can be verified purely by its path with [`verify_cache_entry`].
+    ContentAddressed,
+}
+
+/// Base URL models are downloaded from unless a mirror is configured.
+pub const DEFAULT_MODEL_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// Environment variable naming a mirror base URL to download models from instead of
+/// [`DEFAULT_MODEL_BASE_URL`].
+pub const MODEL_MIRROR_ENV: &str = "WHISPER_STREAM_MODEL_MIRROR";
+
+/// Environment variable that overrides the default cache directory.
+pub const CACHE_DIR_ENV: &str = "WHISPER_STREAM_CACHE";
+
+/// Returns the directory models are cached in, creating it if needed.
+///
+/// This is `$WHISPER_STREAM_CACHE` if set, otherwise `whisper-stream-rs` in the platform's
+/// local data directory.
+pub(crate) fn cache_dir() -> Result<PathBuf, WhisperStreamError> {
+    let cache_dir = match std::env::var_os(CACHE_DIR_ENV).filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::data_local_dir()
+            .ok_or_else(|| WhisperStreamError::Io {
+                source: io::Error::new(io::ErrorKind::NotFound, "Could not find local data dir")
+            })?
+            .join("whisper-stream-rs"),
+    };
+    prepare_cache_dir(&cache_dir)?;
+    Ok(cache_dir)
+}
+
+/// Creates `dir` if needed and checks that models can be written to it.
+///
+/// Fails with [`WhisperStreamError::CacheDirNotWritable`] if the directory can't be created
+/// or a file can't be created in it.
+fn prepare_cache_dir(dir: &Path) -> Result<(), WhisperStreamError> {
+    let not_writable = |source: io::Error| WhisperStreamError::CacheDirNotWritable { path: dir.to_path_buf(), source };
+    fs::create_dir_all(dir).map_err(not_writable)?;
+    // Permission bits don't tell the whole story (ACLs, read-only mounts), so try it.
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    fs::File::create(&probe).map_err(not_writable)?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Name of the file in the cache directory that stores the user's default model.
+const DEFAULT_MODEL_FILE_NAME: &str = "default-model";
+
+/// Persists `model` as the user's default in the cache directory.
+pub fn set_default_model(model: Model) -> Result<(), WhisperStreamError> {
+    let path = cache_dir()?.join(DEFAULT_MODEL_FILE_NAME);
+    fs::write(&path, model.name()).map_err(WhisperStreamError::from)
+}
+
+/// Returns the default model saved with [`set_default_model`], if any.
+///
+/// A missing, unreadable, or unrecognized file is treated as "no default".
+pub fn get_default_model() -> Option<Model> {
+    let path = cache_dir().ok()?.join(DEFAULT_MODEL_FILE_NAME);
+    let contents = fs::read_to_string(path).ok()?;
+    contents.trim().parse().ok()
+}
+
+static OFFLINE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Forbids (or allows again) model downloads for the whole process.
+///
+/// While offline, fetching a model that isn't cached fails with
+/// [`WhisperStreamError::ModelNotCached`] instead of contacting the network, as if every
+/// [`DownloadOptions::offline`] were set.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Returns whether model downloads are disabled with [`set_offline`].
+pub fn is_offline() -> bool {
+    OFFLINE.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Fails with [`WhisperStreamError::ModelNotCached`] for the missing `path` if downloads are disabled.
+fn ensure_online(options: &DownloadOptions, model: Model, path: &Path) -> Result<(), WhisperStreamError> {
+    if options.offline || is_offline() {
+        return Err(WhisperStreamError::ModelNotCached { model: model.to_string(), path: path.to_path_buf() });
+    }
+    Ok(())
+}
+
+/// Returns the user's saved default model, falling back to [`Model::BaseEn`].
+pub fn default_model() -> Model {
+    get_default_model().unwrap_or(Model::BaseEn)
+}
 
 /// Ensures the Whisper model (and CoreML model if 'coreml' feature is enabled) is present, downloading if necessary.
 pub fn ensure_model(model: Model) -> Result<PathBuf, WhisperStreamError> {
-    let cache_dir = dirs::data_local_dir()
-        .ok_or_else(|| WhisperStreamError::Io {
-            source: io::Error::new(io::ErrorKind::NotFound, "Could not find local data dir")
-        })?
-        .join("whisper-stream-rs");
+    ensure_model_with_options(model, &DownloadOptions::default())
+}
+
+/// Callback receiving `(bytes_downloaded, total_bytes)` while a file downloads.
+///
+/// `total_bytes` comes from the `Content-Length` header and is `None` if the server omits it.
+pub type ProgressCallback = Arc<dyn Fn(u64, Option<u64>) + Send + Sync>;
+
+/// Callback receiving `(entries_extracted, total_entries)` while the CoreML encoder zip is
+/// unpacked.
+///
+/// Archives with more than a hundred entries are reported at most once per percent, so the
+/// callback is invoked at most about a hundred times. The last call is always
+/// `(total_entries, total_entries)`.
+pub type ExtractProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Fetches files for the model cache.
+///
+/// [`HttpDownloader`] is used unless [`DownloadOptions::downloader`] supplies another
+/// implementation, e.g. one that copies from an internal artifact store or, in tests, writes
+/// fixture bytes. Downloaded files are still checksummed and moved into place by the cache.
+pub trait Downloader: Send + Sync {
+    /// Fetches `url` into the file at `path`, creating or replacing it.
+    fn download(&self, url: &str, path: &Path) -> Result<(), WhisperStreamError>;
+
+    /// Asks whether `url` changed since it was cached with the `cached` validators, which are
+    /// empty if none were recorded.
+    ///
+    /// Only called for cached models when [`DownloadOptions::check_for_updates`] is set. The
+    /// default answers [`Freshness::Unknown`], which keeps the cached file.
+    fn check_modified(&self, url: &str, cached: &CacheValidators) -> Result<Freshness, WhisperStreamError> {
+        let _ = (url, cached);
+        Ok(Freshness::Unknown)
+    }
+}
+
+/// The `ETag` and `Last-Modified` headers a server sent with a downloaded file.
+///
+/// They are kept in a `.meta` file next to the cached model, so a later
+/// [`Downloader::check_modified`] can ask the server whether the file changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    /// The `ETag` header, quotes included.
+    pub etag: Option<String>,
+    /// The `Last-Modified` header.
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// Returns `true` if the server sent neither header.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        Self { etag: header(reqwest::header::ETAG), last_modified: header(reqwest::header::LAST_MODIFIED) }
+    }
+
+    /// Compares against the validators of the current remote file, preferring the `ETag`.
+    fn freshness_against(&self, current: &CacheValidators) -> Freshness {
+        let same = match (&self.etag, &current.etag, &self.last_modified, &current.last_modified) {
+            (Some(cached), Some(current), _, _) => cached == current,
+            (_, _, Some(cached), Some(current)) => cached == current,
+            _ => return Freshness::Unknown,
+        };
+        if same { Freshness::NotModified } else { Freshness::Modified }
+    }
+}
+
+/// Whether a remote file changed since it was cached; see [`Downloader::check_modified`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// The cached file is current.
+    NotModified,
+    /// A newer file was published; the cached one is replaced.
+    Modified,
+    /// The server gave nothing to compare against; the cached file is kept.
+    Unknown,
+}
+
+/// The default [`Downloader`]: fetches over HTTP(S) with the retry, resume, timeout, proxy,
+/// throttling and progress settings of its [`DownloadOptions`].
+#[derive(Debug, Clone, Default)]
+pub struct HttpDownloader {
+    options: DownloadOptions,
+}
+
+impl HttpDownloader {
+    /// Creates a downloader using `options`. Their `downloader` field is ignored.
+    pub fn new(options: DownloadOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Downloader for HttpDownloader {
+    fn download(&self, url: &str, path: &Path) -> Result<(), WhisperStreamError> {
+        download_file_with_retry(url, path, None, &self.options, self.options.max_retries, self.options.retry_base_delay).map(|_| ())
+    }
+
+    /// Sends a conditional request with `If-None-Match`/`If-Modified-Since`; a `304 Not
+    /// Modified` means the cached file is current. Servers that ignore conditional requests
+    /// answer `200`, whose validators are then compared directly without reading the body.
+    fn check_modified(&self, url: &str, cached: &CacheValidators) -> Result<Freshness, WhisperStreamError> {
+        if cached.is_empty() {
+            return Ok(Freshness::Unknown);
+        }
+        let mut request = download_client(&self.options)?.get(url);
+        if let Some(etag) = &cached.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let resp = request.send()
+            .map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to check {} for updates: {}", url, e)))?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Freshness::NotModified);
+        }
+        if !status.is_success() {
+            return Err(WhisperStreamError::ModelFetch(format!("Failed to check {} for updates: HTTP Status {}", url, status)));
+        }
+        Ok(cached.freshness_against(&CacheValidators::from_headers(resp.headers())))
+    }
+}
+
+/// Options controlling how [`ensure_model_with_options`] stores and fetches a model.
+#[derive(Clone)]
+pub struct DownloadOptions {
+    /// How the model is laid out in the cache directory.
+    pub layout: CacheLayout,
+    /// Maximum download speed in bytes per second, or `None` for no limit.
+    ///
+    /// Useful for background fetching that shouldn't saturate the user's connection.
+    pub max_bytes_per_sec: Option<u64>,
+    /// Called as each chunk of a download arrives. Not called if the model is already cached.
+    pub progress: Option<ProgressCallback>,
+    /// Directory to cache models in instead of the default (see [`CACHE_DIR_ENV`]).
+    pub cache_dir: Option<PathBuf>,
+    /// How often a download is retried after a connection error, interrupted body or 5xx response.
+    pub max_retries: u32,
+    /// Delay before the first retry; it doubles for every further retry.
+    pub retry_base_delay: Duration,
+    /// How long to wait for the connection to the server, or `None` to wait indefinitely.
+    pub connect_timeout: Option<Duration>,
+    /// How long the server may take to respond or go without sending data, or `None` to wait
+    /// indefinitely.
+    ///
+    /// This bounds stalls rather than the whole transfer, so large models on slow connections
+    /// still finish as long as data keeps arriving.
+    pub read_timeout: Option<Duration>,
+    /// Proxy URL to send all download traffic through, e.g. `http://proxy.corp:3128`.
+    ///
+    /// When `None`, the standard `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY` environment variables are
+    /// used. Either way, hosts listed in `NO_PROXY` are contacted directly.
+    pub proxy: Option<String>,
+    /// Base URL of a mirror to download models from, e.g. `https://mirror.example/whisper`.
+    ///
+    /// When `None`, [`MODEL_MIRROR_ENV`] is consulted, falling back to Hugging Face.
+    pub mirror: Option<String>,
+    /// Never download: a model (or CoreML encoder) missing from the cache fails with
+    /// [`WhisperStreamError::ModelNotCached`]. See also [`set_offline`].
+    pub offline: bool,
+    /// Fetches files instead of the built-in HTTP client; `None` uses [`HttpDownloader`].
+    pub downloader: Option<Arc<dyn Downloader>>,
+    /// Called as the CoreML encoder archive is extracted (`coreml` feature only), which can
+    /// take a while for large models.
+    pub extract_progress: Option<ExtractProgressCallback>,
+    /// Aborts the download once cancelled, e.g. from a Ctrl-C handler or a stop button. The
+    /// built-in client checks it between chunks, deletes the `.part` file and fails with
+    /// [`WhisperStreamError::Cancelled`]; a custom [`Downloader`] is only stopped before it starts.
+    pub cancellation_token: Option<CancellationToken>,
+    /// Ask the server whether a cached model changed since it was downloaded, and download it
+    /// again if so. Off by default, since it costs a request every time the model is ensured.
+    ///
+    /// Uses [`Downloader::check_modified`] with the `ETag`/`Last-Modified` recorded at download.
+    /// If the check fails, or the new download does, the cached model is kept.
+    pub check_for_updates: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            layout: CacheLayout::default(),
+            max_bytes_per_sec: None,
+            progress: None,
+            cache_dir: None,
+            max_retries: 3,
+            retry_base_delay: Duration::from_secs(1),
+            connect_timeout: Some(Duration::from_secs(30)),
+            read_timeout: Some(Duration::from_secs(300)),
+            proxy: None,
+            mirror: None,
+            offline: false,
+            downloader: None,
+            extract_progress: None,
+            cancellation_token: None,
+            check_for_updates: false,
+        }
+    }
+}
+
+impl fmt::Debug for DownloadOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DownloadOptions")
+            .field("layout", &self.layout)
+            .field("max_bytes_per_sec", &self.max_bytes_per_sec)
+            .field("progress", &self.progress.as_ref().map(|_| "<callback>"))
+            .field("cache_dir", &self.cache_dir)
+            .field("max_retries", &self.max_retries)
+            .field("retry_base_delay", &self.retry_base_delay)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("proxy", &self.proxy)
+            .field("mirror", &self.mirror)
+            .field("offline", &self.offline)
+            .field("downloader", &self.downloader.as_ref().map(|_| "<downloader>"))
+            .field("extract_progress", &self.extract_progress.as_ref().map(|_| "<callback>"))
+            .field("cancellation_token", &self.cancellation_token)
+            .field("check_for_updates", &self.check_for_updates)
+            .finish()
+    }
+}
+
+/// Like [`ensure_model`], but caches the model in `cache_dir` instead of the default directory.
+///
+/// The directory is created if it doesn't exist.
+pub fn ensure_model_in<P: AsRef<Path>>(model: Model, cache_dir: P) -> Result<PathBuf, WhisperStreamError> {
+    ensure_model_with_options(model, &DownloadOptions { cache_dir: Some(cache_dir.as_ref().to_path_buf()), ..DownloadOptions::default() })
+}
+
+/// Like [`ensure_model`], but reports download progress to `callback`.
+///
+/// `callback` receives `(bytes_downloaded, total_bytes)` as the model file streams in, e.g. to
+/// drive a progress bar; `total_bytes` is `None` if the server doesn't send `Content-Length`.
+pub fn ensure_model_with_progress<F>(model: Model, callback: F) -> Result<PathBuf, WhisperStreamError>
+where
+    F: Fn(u64, Option<u64>) + Send + Sync + 'static,
+{
+    ensure_model_with_options(model, &DownloadOptions { progress: Some(Arc::new(callback)), ..DownloadOptions::default() })
+}
 
-    fs::create_dir_all(&cache_dir).map_err(WhisperStreamError::from)?;
+/// Like [`ensure_model`], but downloads the model from the mirror at `base_url` instead of
+/// Hugging Face.
+///
+/// The mirror must serve the model files under their usual names, e.g.
+/// `<base_url>/ggml-base.en.bin`. Fails with [`WhisperStreamError::ModelFetch`] if `base_url`
+/// is not an `http` or `https` URL.
+pub fn ensure_model_with_mirror(model: Model, base_url: &str) -> Result<PathBuf, WhisperStreamError> {
+    ensure_model_with_options(model, &DownloadOptions { mirror: Some(base_url.to_string()), ..DownloadOptions::default() })
+}
 
+/// Like [`ensure_model`], but fetches the model according to `options`.
+///
+/// This is the entry point for any download setting without a shortcut of its own, e.g.:
+///
+/// - [`DownloadOptions::connect_timeout`] and [`DownloadOptions::read_timeout`]: by default a
+///   connection is given up after 30s and a stalled transfer after 300s.
+/// - [`DownloadOptions::proxy`]: a proxy other than the one in `HTTP_PROXY`/`HTTPS_PROXY`.
+/// - [`DownloadOptions::downloader`]: fetch files with a custom [`Downloader`].
+/// - [`DownloadOptions::layout`]: the [`CacheLayout`]; the returned path is always
+///   `<cache_dir>/<file_name>`, regardless of layout.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use whisper_stream_rs::{ensure_model_with_options, DownloadOptions, Model};
+/// let options = DownloadOptions { read_timeout: Some(Duration::from_secs(30)), ..DownloadOptions::default() };
+/// let path = ensure_model_with_options(Model::BaseEn, &options)?;
+/// # Ok::<(), whisper_stream_rs::WhisperStreamError>(())
+/// ```
+pub fn ensure_model_with_options(model: Model, options: &DownloadOptions) -> Result<PathBuf, WhisperStreamError> {
+    let cache_dir = options_cache_dir(options)?;
     let model_path = cache_dir.join(model.file_name());
 
-    if !model_path.exists() {
-        info!("Downloading Whisper model to {}...", model_path.display());
-        download_file(model.url(), &model_path)?;
-        info!("Whisper model downloaded.");
+    if model_path.exists() && !verify_cached_model(model, &model_path)? {
+        warn!("Cached model {} does not match its checksum; downloading it again.", model_path.display());
+        fs::remove_file(&model_path).map_err(WhisperStreamError::from)?;
+    }
+    remove_stale_part_file(&model_path)?;
+
+    let cached = model_path.exists();
+    if !cached || (options.check_for_updates && remote_model_changed(model, &model_path, options)?) {
+        ensure_online(options, model, &model_path)?;
+        let fetched = match options.layout {
+            CacheLayout::Flat => {
+                check_free_space(model, &model_path)
+                    .and_then(|()| {
+                        info!("Downloading Whisper model to {}...", model_path.display());
+                        download_file(&model_download_url(model, options)?, &model_path, model.expected_sha256(), options)
+                    })
+                    .inspect(|_| info!("Whisper model downloaded."))
+            }
+            CacheLayout::ContentAddressed => {
+                download_content_addressed(model, &cache_dir, &model_path, options)
+            }
+        };
+        match fetched {
+            Ok(fetched) => {
+                fs::write(checksum_path(&model_path), &fetched.digest).map_err(WhisperStreamError::from)?;
+                write_cache_validators(&model_path, &fetched.validators)?;
+            }
+            // A failed update leaves the previous model in place, which still works.
+            Err(e) if cached && !matches!(e, WhisperStreamError::Cancelled) => {
+                warn!("Failed to update {} ({}); keeping the cached model.", model_path.display(), e);
+            }
+            Err(e) => return Err(e),
+        }
     }
 
     #[cfg(feature = "coreml")]
     {
-        ensure_coreml_model_if_enabled(&cache_dir)?;
+        ensure_coreml_model_if_enabled(model, &cache_dir, options)?;
     }
 
     Ok(model_path) // Return path to the main .bin model
 }
 
-#[cfg(feature = "coreml")]
-fn ensure_coreml_model_if_enabled(cache_dir: &Path) -> Result<(), WhisperStreamError> {
-    info!("CoreML feature enabled. Checking for CoreML model...");
-    let coreml_base_name = BASE_MODEL_NAME_FOR_COREML;
-    let coreml_encoder_dir_name = format!("{}-encoder.mlmodelc", coreml_base_name);
-    let coreml_model_dir_path = cache_dir.join(&coreml_encoder_dir_name);
+/// Most model downloads [`ensure_models_with_options`] runs at the same time.
+const MAX_PARALLEL_DOWNLOADS: usize = 4;
 
-    if !coreml_model_dir_path.exists() {
-        let coreml_model_zip_url = COREML_MODEL_URL_TEMPLATE.replace("{}", coreml_base_name);
-        let coreml_zip_filename = format!("{}-encoder.mlmodelc.zip", coreml_base_name);
-        let coreml_zip_path = cache_dir.join(&coreml_zip_filename);
+/// Like [`ensure_model`], but for several models at once, downloading missing ones in parallel.
+///
+/// See [`ensure_models_with_options`].
+pub fn ensure_models(models: &[Model]) -> Result<Vec<PathBuf>, WhisperStreamError> {
+    ensure_models_with_options(models, &DownloadOptions::default())
+}
 
-        info!("Downloading CoreML model from {} to {}...", coreml_model_zip_url, coreml_zip_path.display());
-        download_file(&coreml_model_zip_url, &coreml_zip_path)?;
-        info!("CoreML model ZIP downloaded.");
+/// Ensures every model in `models` is cached, fetching up to four at a time, and returns their
+/// paths in the same order as `models`.
+///
+/// Each model is handled once even if listed several times, and models already in the cache
+/// are only verified, not downloaded again. All models are attempted even if some fail: a
+/// single failure is returned as is, several are combined into one
+/// [`WhisperStreamError::ModelFetch`] naming every failed model.
+pub fn ensure_models_with_options(models: &[Model], options: &DownloadOptions) -> Result<Vec<PathBuf>, WhisperStreamError> {
+    let mut unique: Vec<Model> = Vec::new();
+    for &model in models {
+        if !unique.contains(&model) {
+            unique.push(model);
+        }
+    }
 
-        info!("Unzipping CoreML model to {}...", cache_dir.display());
-        if let Err(e) = unzip_file(&coreml_zip_path, &cache_dir) {
-            // Attempt to clean up the potentially corrupted zip file or partial extraction
-            if let Err(remove_err) = fs::remove_file(&coreml_zip_path) {
-                warn!("Failed to remove zip file {} during cleanup: {}", coreml_zip_path.display(), remove_err);
-            }
-            if let Err(remove_dir_err) = fs::remove_dir_all(&coreml_model_dir_path) {
-                warn!("Failed to remove directory {} during cleanup: {}", coreml_model_dir_path.display(), remove_dir_err);
-            }
-            // The error is returned from this function, so no need for error! here, caller handles it.
-            return Err(e);
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let mut results: Vec<(usize, Result<PathBuf, WhisperStreamError>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..MAX_PARALLEL_DOWNLOADS.min(unique.len()))
+            .map(|_| scope.spawn(|| {
+                let mut done = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    let Some(&model) = unique.get(index) else { return done };
+                    done.push((index, ensure_model_with_options(model, options)));
+                }
+            }))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    results.sort_by_key(|(index, _)| *index);
+
+    let mut paths = Vec::with_capacity(unique.len());
+    let mut failures = Vec::new();
+    for (index, result) in results {
+        match result {
+            Ok(path) => paths.push(path),
+            Err(e) => failures.push((unique[index], e)),
         }
-        info!("CoreML model unzipped and available at {}.", coreml_model_dir_path.display());
+    }
+    if failures.len() == 1 {
+        return Err(failures.remove(0).1);
+    }
+    if !failures.is_empty() {
+        let details: Vec<String> = failures.iter().map(|(model, e)| format!("{}: {}", model, e)).collect();
+        return Err(WhisperStreamError::ModelFetch(format!(
+            "{} of {} model downloads failed: {}", failures.len(), unique.len(), details.join("; ")
+        )));
+    }
+    Ok(models.iter()
+        .map(|model| paths[unique.iter().position(|m| m == model).unwrap_or_default()].clone())
+        .collect())
+}
 
-        // Clean up the downloaded zip file after successful extraction
-        if fs::remove_file(&coreml_zip_path).is_err() {
-            warn!("Could not remove CoreML zip file: {}", coreml_zip_path.display());
+/// Returns the cache directory `options` ask for, or the default one, creating it if needed.
+fn options_cache_dir(options: &DownloadOptions) -> Result<PathBuf, WhisperStreamError> {
+    match &options.cache_dir {
+        Some(dir) => {
+            prepare_cache_dir(dir)?;
+            Ok(dir.clone())
         }
-    } else {
-        info!("CoreML model already present at {}.", coreml_model_dir_path.display());
+        None => cache_dir(),
     }
-    Ok(())
 }
 
-fn download_file(url: &str, path: &Path) -> Result<(), WhisperStreamError> {
-    let mut resp = reqwest::blocking::get(url)
-        .map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to initiate download from {}: {}", url, e)))?;
+/// Outcome of [`prefetch_all`]: the models now cached and the ones that failed.
+#[derive(Debug, Default)]
+pub struct PrefetchReport {
+    /// Models that are cached, with their paths, in [`Model::all`] order.
+    pub ready: Vec<(Model, PathBuf)>,
+    /// Models that could not be fetched, with the reason.
+    pub failed: Vec<(Model, WhisperStreamError)>,
+}
 
-    if !resp.status().is_success() {
-        return Err(WhisperStreamError::ModelFetch(format!("Failed to download from {}: HTTP Status {}", url, resp.status())));
+impl PrefetchReport {
+    /// Returns `true` if every model is cached.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Paths of the cached models.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.ready.iter().map(|(_, path)| path.clone()).collect()
     }
+}
+
+/// Downloads every supported model, e.g. for a first-run setup wizard. See
+/// [`prefetch_all_with_options`].
+pub fn prefetch_all<F>(progress: F) -> Result<PrefetchReport, WhisperStreamError>
+where
+    F: Fn(u64, u64) + Send + Sync + 'static,
+{
+    prefetch_all_with_options(&DownloadOptions::default(), progress)
+}
+
+/// Ensures every model in [`Model::all`] is cached, one after another, and reports which ones
+/// are ready and which failed.
+///
+/// `progress` receives `(bytes_done, total_bytes)` across all models that were missing, based
+/// on their [`Model::download_size_bytes`]; it replaces [`DownloadOptions::progress`].
+/// Already cached models are only verified. A failing model doesn't stop the others; only
+/// an unusable cache directory or cancellation through
+/// [`DownloadOptions::cancellation_token`] ends the run with an error.
+pub fn prefetch_all_with_options<F>(options: &DownloadOptions, progress: F) -> Result<PrefetchReport, WhisperStreamError>
+where
+    F: Fn(u64, u64) + Send + Sync + 'static,
+{
+    let cache_dir = options_cache_dir(options)?;
+    let missing: Vec<Model> = Model::all().into_iter()
+        .filter(|model| !cache_dir.join(model.file_name()).exists())
+        .collect();
+    let total: u64 = missing.iter().map(Model::download_size_bytes).sum();
+    let progress = Arc::new(progress);
 
-    let mut out = fs::File::create(path)
-        .map_err(|e| WhisperStreamError::Io { source: e })?;
+    let mut report = PrefetchReport::default();
+    let mut done = 0;
+    for model in Model::all() {
+        let mut model_options = DownloadOptions { cache_dir: Some(cache_dir.clone()), progress: None, ..options.clone() };
+        let size = model.download_size_bytes();
+        let is_missing = missing.contains(&model);
+        if is_missing {
+            let (progress, start) = (progress.clone(), done);
+            // Sizes are nominal, so keep each model's bytes within its share of the total.
+            model_options.progress = Some(Arc::new(move |bytes, _| progress(start + bytes.min(size), total)));
+        }
+        match ensure_model_with_options(model, &model_options) {
+            Ok(path) => report.ready.push((model, path)),
+            Err(WhisperStreamError::Cancelled) => return Err(WhisperStreamError::Cancelled),
+            Err(e) => {
+                warn!("Failed to prefetch model {}: {}", model, e);
+                report.failed.push((model, e));
+            }
+        }
+        if is_missing {
+            done += size;
+            progress(done, total);
+        }
+    }
+    Ok(report)
+}
 
-    io::copy(&mut resp, &mut out)
-        .map_err(|e| WhisperStreamError::Io { source: e })?;
+/// Returns the URL to download `model` from, honoring the mirror in `options` or [`MODEL_MIRROR_ENV`].
+fn model_download_url(model: Model, options: &DownloadOptions) -> Result<String, WhisperStreamError> {
+    let mirror = options.mirror.clone()
+        .or_else(|| std::env::var(MODEL_MIRROR_ENV).ok().filter(|v| !v.is_empty()));
+    match mirror {
+        Some(base_url) => {
+            validate_mirror_url(&base_url)?;
+            Ok(model.url_with_base(&base_url))
+        }
+        None => Ok(model.url().to_string()),
+    }
+}
 
-    out.flush().map_err(|e| WhisperStreamError::Io { source: e })?;
+/// Checks that `base_url` is an absolute `http` or `https` URL.
+fn validate_mirror_url(base_url: &str) -> Result<(), WhisperStreamError> {
+    let url = reqwest::Url::parse(base_url)
+        .map_err(|e| WhisperStreamError::ModelFetch(format!("Invalid model mirror URL {:?}: {}", base_url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(WhisperStreamError::ModelFetch(format!(
+            "Invalid model mirror URL {:?}: expected an http or https URL", base_url
+        )));
+    }
     Ok(())
 }
 
-#[cfg(feature = "coreml")]
-fn unzip_file(zip_path: &Path, dest_dir: &Path) -> Result<(), WhisperStreamError> {
-    let file = File::open(zip_path).map_err(|e| WhisperStreamError::Io { source: e })?;
-    let mut archive = ZipArchive::new(file).map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to open zip archive '{}': {}", zip_path.display(), e)))?;
+/// Returns the path of the file recording the SHA-256 digest of `model_path`.
+fn checksum_path(model_path: &Path) -> PathBuf {
+    append_to_file_name(model_path, &format!(".{}", CHECKSUM_EXTENSION))
+}
 
-    for i in 0..archive.len() {
-        let mut file_in_zip = archive.by_index(i).map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to access file in zip '{}': {}", zip_path.display(), e)))?;
-        let outpath = match file_in_zip.enclosed_name() {
-            Some(path) => dest_dir.join(path),
-            None => continue, // Skip if path is risky (e.g. ../)
+/// Returns `path` with `suffix` appended to its file name, keeping the existing extension.
+fn append_to_file_name(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Returns the path of the file recording the [`CacheValidators`] of `model_path`.
+fn meta_path(model_path: &Path) -> PathBuf {
+    append_to_file_name(model_path, ".meta")
+}
+
+/// Reads the validators recorded for `model_path`, or empty ones if there are none.
+fn read_cache_validators(model_path: &Path) -> CacheValidators {
+    let mut validators = CacheValidators::default();
+    let Ok(contents) = fs::read_to_string(meta_path(model_path)) else { return validators };
+    for line in contents.lines() {
+        match line.split_once(": ") {
+            Some(("ETag", value)) => validators.etag = Some(value.to_string()),
+            Some(("Last-Modified", value)) => validators.last_modified = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    validators
+}
+
+/// Records `validators` for `model_path` as header lines, removing stale ones if they are empty.
+fn write_cache_validators(model_path: &Path, validators: &CacheValidators) -> Result<(), WhisperStreamError> {
+    let path = meta_path(model_path);
+    if validators.is_empty() {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
         };
+    }
+    let mut contents = String::new();
+    if let Some(etag) = &validators.etag {
+        contents.push_str(&format!("ETag: {}\n", etag));
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        contents.push_str(&format!("Last-Modified: {}\n", last_modified));
+    }
+    fs::write(path, contents).map_err(WhisperStreamError::from)
+}
 
-        if file_in_zip.name().ends_with('/') {
-            fs::create_dir_all(&outpath).map_err(|e| WhisperStreamError::Io { source: e })?;
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p).map_err(|e| WhisperStreamError::Io { source: e })?;
-                }
+/// Asks the server whether the cached `model_path` is outdated, for
+/// [`DownloadOptions::check_for_updates`].
+///
+/// Offline, or if the check fails, the cached model counts as current. Only a cancelled
+/// check is reported as an error.
+fn remote_model_changed(model: Model, model_path: &Path, options: &DownloadOptions) -> Result<bool, WhisperStreamError> {
+    if options.offline || is_offline() {
+        return Ok(false);
+    }
+    if is_cancelled(options) {
+        return Err(WhisperStreamError::Cancelled);
+    }
+    let url = model_download_url(model, options)?;
+    let cached = read_cache_validators(model_path);
+    let freshness = match &options.downloader {
+        Some(downloader) => downloader.check_modified(&url, &cached),
+        None => HttpDownloader::new(options.clone()).check_modified(&url, &cached),
+    };
+    match freshness {
+        Ok(Freshness::Modified) => {
+            info!("A newer version of {} is available; downloading it.", model);
+            Ok(true)
+        }
+        Ok(_) => Ok(false),
+        Err(e) => {
+            warn!("Could not check {} for updates ({}); using the cached model.", model, e);
+            Ok(false)
+        }
+    }
+}
+
+/// Returns a string identifying the contents of the model file at `path`, for
+/// [`TranscribeOptions::model_id`](crate::TranscribeOptions::model_id).
+///
+/// Models in the cache are identified by the SHA-256 recorded when they were downloaded, so
+/// an updated model gets a new identity. Other files, such as fine-tuned models, are
+/// identified by their absolute path, size and modification time, which is cheap to compute
+/// for large files.
+pub fn model_identity<P: AsRef<Path>>(path: P) -> Result<String, WhisperStreamError> {
+    let path = path.as_ref();
+    if let Ok(digest) = fs::read_to_string(checksum_path(path)) {
+        return Ok(format!("sha256:{}", digest.trim()));
+    }
+    let metadata = fs::metadata(path).map_err(WhisperStreamError::from)?;
+    let modified = metadata.modified().ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0, |since_epoch| since_epoch.as_nanos());
+    let path = fs::canonicalize(path).map_err(WhisperStreamError::from)?;
+    Ok(format!("file:{}:{}:{}", path.display(), metadata.len(), modified))
+}
+
+/// Checks a cached model against its pinned digest, or else the digest recorded at download.
+///
+/// A file with neither, e.g. one left by a version that recorded no digests, is only rejected
+/// if it is shorter than the model's download size, which catches interrupted downloads.
+fn verify_cached_model(model: Model, model_path: &Path) -> Result<bool, WhisperStreamError> {
+    let expected = match model.expected_sha256() {
+        Some(pinned) => pinned.to_string(),
+        None => match fs::read_to_string(checksum_path(model_path)) {
+            Ok(recorded) => recorded.trim().to_string(),
+            Err(_) => {
+                let len = fs::metadata(model_path).map_err(WhisperStreamError::from)?.len();
+                return Ok(len >= model.download_size_bytes());
+            }
+        },
+    };
+    Ok(sha256_file(model_path)?.eq_ignore_ascii_case(&expected))
+}
+
+/// Removes the `.part` file of `path` if `path` itself is already complete.
+///
+/// Such a file can only be left over from an earlier run and would never be resumed. A `.part`
+/// file without a finished download next to it is kept, so the next download resumes from it.
+fn remove_stale_part_file(path: &Path) -> Result<(), WhisperStreamError> {
+    let part_path = part_path(path);
+    if path.exists() && part_path.exists() {
+        info!("Removing stale partial download {}.", part_path.display());
+        fs::remove_file(&part_path).map_err(WhisperStreamError::from)?;
+    }
+    Ok(())
+}
+
+/// Fails with [`WhisperStreamError::ChecksumMismatch`], deleting `path`, if `digest` differs
+/// from `expected`. Without an expected digest every file passes.
+fn check_digest(expected: Option<&str>, path: &Path, digest: &str) -> Result<(), WhisperStreamError> {
+    match expected {
+        Some(expected) if !expected.eq_ignore_ascii_case(digest) => {
+            if let Err(e) = fs::remove_file(path) {
+                warn!("Failed to remove corrupt download {}: {}", path.display(), e);
             }
-            let mut outfile = fs::File::create(&outpath).map_err(|e| WhisperStreamError::Io { source: e })?;
-            io::copy(&mut file_in_zip, &mut outfile).map_err(|e| WhisperStreamError::Io { source: e })?;
+            Err(WhisperStreamError::ChecksumMismatch { expected: expected.to_string(), actual: digest.to_string() })
         }
+        _ => Ok(()),
+    }
+}
+
+/// Validates a model file at an explicit local path and returns it, without any network access.
+///
+/// Use this for models kept on a network share, custom fine-tuned models, or air-gapped
+/// machines. The file must exist and start with a ggml header; quantized files are accepted.
+pub fn ensure_model_from_path<P: AsRef<Path>>(path: P) -> Result<PathBuf, WhisperStreamError> {
+    let path = path.as_ref();
+    if !path.is_file() {
+        return Err(WhisperStreamError::ModelLoad(format!("Model file {} does not exist or is not a file", path.display())));
+    }
+    read_ggml_header(path).map_err(WhisperStreamError::ModelLoad)?;
+    Ok(path.to_path_buf())
+}
+
+/// Downloads `model` into `by-hash/<sha256>` and links `model_path` to it, returning the digest.
+///
+/// An in-progress download is kept as `by-hash/<file_name>.download`, so an interrupted run
+/// never leaves a partial file under a hash name.
+fn download_content_addressed(model: Model, cache_dir: &Path, model_path: &Path, options: &DownloadOptions) -> Result<Fetched, WhisperStreamError> {
+    let by_hash_dir = cache_dir.join(BY_HASH_DIR_NAME);
+    fs::create_dir_all(&by_hash_dir).map_err(WhisperStreamError::from)?;
+
+    let download_path = by_hash_dir.join(format!("{}.download", model.file_name()));
+    check_free_space(model, &download_path)?;
+    info!("Downloading Whisper model to {}...", download_path.display());
+    let fetched = download_file(&model_download_url(model, options)?, &download_path, model.expected_sha256(), options)?;
+    let hashed_path = by_hash_dir.join(&fetched.digest);
+    if hashed_path.exists() {
+        info!("Content-addressed entry {} already present, discarding duplicate download.", fetched.digest);
+        fs::remove_file(&download_path).map_err(WhisperStreamError::from)?;
+    } else {
+        fs::rename(&download_path, &hashed_path).map_err(WhisperStreamError::from)?;
+    }
+
+    // An updated model replaces the link to the previous version.
+    if model_path.exists() {
+        fs::remove_file(model_path).map_err(WhisperStreamError::from)?;
+    }
+    if let Err(e) = fs::hard_link(&hashed_path, model_path) {
+        warn!("Could not hard link {} to {} ({}), copying instead.", model_path.display(), hashed_path.display(), e);
+        fs::copy(&hashed_path, model_path).map_err(WhisperStreamError::from)?;
+    }
+    info!("Whisper model stored as {}.", hashed_path.display());
+    Ok(fetched)
+}
+
+/// Verifies a content-addressed cache entry by re-hashing it and comparing against its file name.
+///
+/// Returns `Ok(false)` if the contents no longer match the SHA-256 encoded in the path.
+pub fn verify_cache_entry(path: &Path) -> Result<bool, WhisperStreamError> {
+    let expected = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| WhisperStreamError::Internal(format!("Cache entry path has no file name: {}", path.display())))?;
+    Ok(sha256_file(path)?.eq_ignore_ascii_case(expected))
+}
+
+/// A model found in the cache by [`list_cached_models`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedModel {
+    /// The cached model.
+    pub model: Model,
+    /// Path of the cached model file.
+    pub path: PathBuf,
+    /// Whether the CoreML encoder whisper.cpp looks for next to the model file is present.
+    pub coreml_encoder: bool,
+}
+
+/// Returns the path of `model` if it is cached and intact, without ever downloading it.
+///
+/// Gives `Some(path)` exactly when [`ensure_model`] would use the cached file as is, and
+/// `None` when it would start a download, so tooling can ask before fetching a large model.
+/// The file is checksummed like in [`ensure_model`], which takes a moment for large models.
+pub fn ensure_model_check_only(model: Model) -> Result<Option<PathBuf>, WhisperStreamError> {
+    ensure_model_check_only_in(model, &cache_dir()?)
+}
+
+/// Returns `true` if [`ensure_model_check_only`] finds `model` in the cache.
+///
+/// Errors, such as an unusable cache directory, count as not cached.
+pub fn is_model_cached(model: Model) -> bool {
+    matches!(ensure_model_check_only(model), Ok(Some(_)))
+}
+
+fn ensure_model_check_only_in(model: Model, cache_dir: &Path) -> Result<Option<PathBuf>, WhisperStreamError> {
+    let model_path = cache_dir.join(model.file_name());
+    if model_path.is_file() && verify_cached_model(model, &model_path)? {
+        Ok(Some(model_path))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the models already downloaded to the cache directory, in [`Model::list`] order.
+///
+/// Only complete downloads are listed; the file is not checksummed, which
+/// [`ensure_model`] does before using it.
+pub fn list_cached_models() -> Result<Vec<CachedModel>, WhisperStreamError> {
+    Ok(list_cached_models_in(&cache_dir()?))
+}
+
+fn list_cached_models_in(cache_dir: &Path) -> Vec<CachedModel> {
+    Model::list().into_iter()
+        .map(|model| (model, cache_dir.join(model.file_name())))
+        .filter(|(_, path)| path.is_file())
+        .map(|(model, path)| {
+            let encoder = format!("{}-encoder.mlmodelc", coreml_base_name(model.file_name().trim_end_matches(".bin")));
+            CachedModel { model, coreml_encoder: cache_dir.join(encoder).is_dir(), path }
+        })
+        .collect()
+}
+
+/// Removes every cached model and CoreML encoder, returning the number of bytes freed.
+///
+/// Only files this crate manages are touched: the known model files with their checksum, `.meta`
+/// and `.part` files, content-addressed entries, and CoreML encoders. Anything else in the cache
+/// directory, such as the saved default model, is left alone.
+pub fn clear_model_cache() -> Result<u64, WhisperStreamError> {
+    clear_model_cache_in(&cache_dir()?)
+}
+
+/// Removes `model` (and its CoreML encoder, if any) from the cache, returning the number of
+/// bytes freed. Removing a model that isn't cached frees nothing and is not an error.
+pub fn remove_model(model: Model) -> Result<u64, WhisperStreamError> {
+    remove_model_in(model, &cache_dir()?)
+}
+
+fn clear_model_cache_in(cache_dir: &Path) -> Result<u64, WhisperStreamError> {
+    let mut freed = 0;
+    for model in Model::list() {
+        freed += remove_model_in(model, cache_dir)?;
+    }
+    // Leftover content-addressed entries and interrupted downloads of them.
+    let by_hash_dir = cache_dir.join(BY_HASH_DIR_NAME);
+    if by_hash_dir.is_dir() {
+        for entry in fs::read_dir(&by_hash_dir).map_err(WhisperStreamError::from)? {
+            freed += remove_cached_path(&entry.map_err(WhisperStreamError::from)?.path())?;
+        }
+        fs::remove_dir(&by_hash_dir).map_err(WhisperStreamError::from)?;
+    }
+    Ok(freed)
+}
+
+fn remove_model_in(model: Model, cache_dir: &Path) -> Result<u64, WhisperStreamError> {
+    let model_path = cache_dir.join(model.file_name());
+    let mut freed = 0;
+    // Remove the link first so a content-addressed entry is counted once, when its last link goes.
+    if let Ok(digest) = fs::read_to_string(checksum_path(&model_path)) {
+        let hashed_path = cache_dir.join(BY_HASH_DIR_NAME).join(digest.trim());
+        freed += remove_cached_path(&model_path)?;
+        freed += remove_cached_path(&hashed_path)?;
+    }
+    let stem = model.file_name().trim_end_matches(".bin");
+    let by_hash_download = cache_dir.join(BY_HASH_DIR_NAME).join(format!("{}.download", model.file_name()));
+    for path in [
+        model_path.clone(),
+        checksum_path(&model_path),
+        meta_path(&model_path),
+        part_path(&model_path),
+        part_path(&by_hash_download),
+        by_hash_download,
+        cache_dir.join(format!("{}-encoder.mlmodelc", stem)),
+        cache_dir.join(format!("{}-encoder.mlmodelc.zip", stem)),
+    ] {
+        freed += remove_cached_path(&path)?;
+    }
+    Ok(freed)
+}
+
+/// Removes the file or directory at `path` if it exists, returning the bytes freed.
+///
+/// A file with other hard links frees nothing, since its contents stay on disk.
+fn remove_cached_path(path: &Path) -> Result<u64, WhisperStreamError> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+    if metadata.is_dir() {
+        let mut freed = 0;
+        for entry in fs::read_dir(path).map_err(WhisperStreamError::from)? {
+            freed += remove_cached_path(&entry.map_err(WhisperStreamError::from)?.path())?;
+        }
+        fs::remove_dir(path).map_err(WhisperStreamError::from)?;
+        return Ok(freed);
+    }
+    #[cfg(unix)]
+    let shared = std::os::unix::fs::MetadataExt::nlink(&metadata) > 1;
+    #[cfg(not(unix))]
+    let shared = false;
+    fs::remove_file(path).map_err(WhisperStreamError::from)?;
+    Ok(if shared { 0 } else { metadata.len() })
+}
+
+/// Fails with [`WhisperStreamError::InsufficientSpace`] if the volume holding `download_path`
+/// can't fit the rest of `model`'s download, counting bytes already in its `.part` file.
+///
+/// If the free space can't be queried, the download goes ahead with a warning.
+fn check_free_space(model: Model, download_path: &Path) -> Result<(), WhisperStreamError> {
+    let dir = download_path.parent().unwrap_or(Path::new("."));
+    let resumed = fs::metadata(part_path(download_path)).map(|m| m.len()).unwrap_or(0);
+    let needed = model.download_size_bytes().saturating_sub(resumed);
+    match fs2::available_space(dir) {
+        Ok(available) => ensure_space(needed, available),
+        Err(e) => {
+            warn!("Could not determine free space in {} ({}); downloading anyway.", dir.display(), e);
+            Ok(())
+        }
+    }
+}
+
+/// Compares a download size against the free space reported for its volume.
+fn ensure_space(needed: u64, available: u64) -> Result<(), WhisperStreamError> {
+    if available < needed {
+        return Err(WhisperStreamError::InsufficientSpace { needed, available });
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Computes the lowercase hex SHA-256 digest of a file without loading it fully into memory.
+fn sha256_file(path: &Path) -> Result<String, WhisperStreamError> {
+    let mut file = fs::File::open(path).map_err(WhisperStreamError::from)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(WhisperStreamError::from)?;
+    Ok(hex_digest(hasher))
+}
+
+/// Formats the digest of `hasher` as lowercase hex.
+fn hex_digest(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(feature = "coreml")]
+fn ensure_coreml_model_if_enabled(model: Model, cache_dir: &Path, options: &DownloadOptions) -> Result<(), WhisperStreamError> {
+    info!("CoreML feature enabled. Checking for CoreML model...");
+    let Some(coreml_base_name) = published_coreml_base_name(model) else {
+        return Ok(());
+    };
+    let coreml_encoder_dir_name = format!("{}-encoder.mlmodelc", coreml_base_name);
+    let coreml_model_dir_path = cache_dir.join(&coreml_encoder_dir_name);
+
+    if !coreml_model_dir_path.exists() {
+        ensure_online(options, model, &coreml_model_dir_path)?;
+        let coreml_model_zip_url = COREML_MODEL_URL_TEMPLATE.replace("{}", coreml_base_name);
+        let coreml_zip_filename = format!("{}-encoder.mlmodelc.zip", coreml_base_name);
+        let coreml_zip_path = cache_dir.join(&coreml_zip_filename);
+
+        info!("Downloading CoreML model from {} to {}...", coreml_model_zip_url, coreml_zip_path.display());
+        download_file(&coreml_model_zip_url, &coreml_zip_path, None, options)?;
+        info!("CoreML model ZIP downloaded.");
+
+        extract_coreml_zip(&coreml_zip_path, cache_dir, &coreml_model_dir_path, options.extract_progress.as_ref())?;
+    } else {
+        info!("CoreML model already present at {}.", coreml_model_dir_path.display());
+    }
+    Ok(())
+}
+
+/// Returns the CoreML encoder base name for `model`, or `None` with a warning if no encoder is
+/// published for it.
+#[cfg(feature = "coreml")]
+fn published_coreml_base_name(model: Model) -> Option<&'static str> {
+    let base_name = coreml_base_name(model.file_name().trim_end_matches(".bin"));
+    if COREML_ENCODER_BASE_NAMES.contains(&base_name) {
+        Some(base_name)
+    } else {
+        warn!("No CoreML encoder is published for model {}; whisper.cpp will run the encoder without CoreML.", model);
+        None
+    }
+}
+
+/// Files every compiled CoreML model directory contains; without them whisper.cpp fails to
+/// load the encoder.
+#[cfg(any(feature = "coreml", test))]
+const REQUIRED_COREML_FILES: &[&str] = &["coremldata.bin", "model.mil"];
+
+/// Unzips the downloaded CoreML archive into `cache_dir`, checks that it produced a complete
+/// `coreml_model_dir_path`, then removes the archive.
+///
+/// On failure, the archive and any partial extraction are removed.
+#[cfg(any(feature = "coreml", test))]
+fn extract_coreml_zip(coreml_zip_path: &Path, cache_dir: &Path, coreml_model_dir_path: &Path, progress: Option<&ExtractProgressCallback>) -> Result<(), WhisperStreamError> {
+    info!("Unzipping CoreML model to {}...", cache_dir.display());
+    let extracted = unzip_file(coreml_zip_path, cache_dir, progress.map(|p| p.as_ref() as &dyn Fn(usize, usize)))
+        .and_then(|()| check_coreml_model_dir(coreml_model_dir_path));
+    if let Err(e) = extracted {
+        // Attempt to clean up the potentially corrupted zip file or partial extraction
+        if let Err(remove_err) = fs::remove_file(coreml_zip_path) {
+            warn!("Failed to remove zip file {} during cleanup: {}", coreml_zip_path.display(), remove_err);
+        }
+        if coreml_model_dir_path.exists() && let Err(remove_dir_err) = fs::remove_dir_all(coreml_model_dir_path) {
+            warn!("Failed to remove directory {} during cleanup: {}", coreml_model_dir_path.display(), remove_dir_err);
+        }
+        // The error is returned from this function, so no need for error! here, caller handles it.
+        return Err(e);
+    }
+    info!("CoreML model unzipped and available at {}.", coreml_model_dir_path.display());
+
+    // Clean up the downloaded zip file after successful extraction
+    if fs::remove_file(coreml_zip_path).is_err() {
+        warn!("Could not remove CoreML zip file: {}", coreml_zip_path.display());
+    }
+    Ok(())
+}
+
+/// Fails with [`WhisperStreamError::ModelFetch`] if `dir` lacks any of [`REQUIRED_COREML_FILES`].
+#[cfg(any(feature = "coreml", test))]
+fn check_coreml_model_dir(dir: &Path) -> Result<(), WhisperStreamError> {
+    let missing: Vec<&str> = REQUIRED_COREML_FILES.iter()
+        .copied()
+        .filter(|name| !dir.join(name).is_file())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(WhisperStreamError::ModelFetch(format!(
+        "CoreML model {} is incomplete, missing: {}", dir.display(), missing.join(", ")
+    )))
+}
+
+/// Async version of [`ensure_model`] for use inside a tokio runtime.
+///
+/// The download streams to disk through reqwest's async client and `tokio::fs`, while
+/// checksumming and CoreML extraction run on the blocking thread pool, so the executor is
+/// never stalled. Downloads use the default [`DownloadOptions`] timeouts and proxy settings;
+/// resuming, retries, throttling and progress reporting are only available in the blocking API.
+#[cfg(feature = "async")]
+pub async fn ensure_model_async(model: Model) -> Result<PathBuf, WhisperStreamError> {
+    let options = DownloadOptions::default();
+    let cache_dir = spawn_blocking(cache_dir).await?;
+    let model_path = cache_dir.join(model.file_name());
+
+    if tokio::fs::try_exists(&model_path).await.map_err(WhisperStreamError::from)? {
+        let path = model_path.clone();
+        if spawn_blocking(move || verify_cached_model(model, &path)).await? {
+            #[cfg(feature = "coreml")]
+            ensure_coreml_model_async(model, &cache_dir, &options).await?;
+            return Ok(model_path);
+        }
+        warn!("Cached model {} does not match its checksum; downloading it again.", model_path.display());
+        tokio::fs::remove_file(&model_path).await.map_err(WhisperStreamError::from)?;
+    }
+
+    ensure_online(&options, model, &model_path)?;
+    let path = model_path.clone();
+    spawn_blocking(move || check_free_space(model, &path)).await?;
+    info!("Downloading Whisper model to {}...", model_path.display());
+    let digest = download_file_async(&model_download_url(model, &options)?, &model_path, model.expected_sha256(), &options).await?;
+    tokio::fs::write(checksum_path(&model_path), &digest).await.map_err(WhisperStreamError::from)?;
+    info!("Whisper model downloaded.");
+
+    #[cfg(feature = "coreml")]
+    ensure_coreml_model_async(model, &cache_dir, &options).await?;
+
+    Ok(model_path)
+}
+
+/// Runs blocking filesystem work on tokio's blocking thread pool.
+#[cfg(feature = "async")]
+async fn spawn_blocking<T, F>(f: F) -> Result<T, WhisperStreamError>
+where
+    F: FnOnce() -> Result<T, WhisperStreamError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await
+        .map_err(|e| WhisperStreamError::Internal(format!("Blocking model task failed: {}", e)))?
+}
+
+/// Streams `url` into `path` with the async client, via a `.part` file like [`download_file`],
+/// and returns the SHA-256 digest of the downloaded bytes.
+///
+/// If `expected_sha256` is given, a mismatching download is deleted before it reaches `path`.
+#[cfg(feature = "async")]
+async fn download_file_async(url: &str, path: &Path, expected_sha256: Option<&str>, options: &DownloadOptions) -> Result<String, WhisperStreamError> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = options.connect_timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = options.read_timeout {
+        builder = builder.read_timeout(timeout);
+    }
+    if let Some(proxy) = &options.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| WhisperStreamError::ModelFetch(format!("Invalid proxy URL {}: {}", proxy, e)))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build()
+        .map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to create HTTP client: {}", e)))?;
+
+    let mut resp = client.get(url).send().await
+        .map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to initiate download from {}: {}", url, e)))?;
+    if !resp.status().is_success() {
+        return Err(WhisperStreamError::ModelFetch(format!("Failed to download from {}: HTTP Status {}", url, resp.status())));
+    }
+    let expected_len = resp.content_length();
+
+    let part_path = part_path(path);
+    let mut out = tokio::fs::File::create(&part_path).await.map_err(WhisperStreamError::from)?;
+    let mut hasher = Sha256::new();
+    let mut written = 0u64;
+    while let Some(chunk) = resp.chunk().await
+        .map_err(|e| WhisperStreamError::ModelFetch(format!("Download from {} failed: {}", url, e)))?
+    {
+        out.write_all(&chunk).await.map_err(WhisperStreamError::from)?;
+        hasher.update(&chunk);
+        written += chunk.len() as u64;
+    }
+    out.flush().await.map_err(WhisperStreamError::from)?;
+    drop(out);
+
+    if let Some(expected_len) = expected_len
+        && written != expected_len
+    {
+        return Err(WhisperStreamError::ModelFetch(format!(
+            "Download from {} was interrupted at {} of {} bytes", url, written, expected_len
+        )));
+    }
+    let digest = hex_digest(hasher);
+    check_digest(expected_sha256, &part_path, &digest)?;
+    tokio::fs::rename(&part_path, path).await.map_err(WhisperStreamError::from)?;
+    Ok(digest)
+}
+
+#[cfg(all(feature = "async", feature = "coreml"))]
+async fn ensure_coreml_model_async(model: Model, cache_dir: &Path, options: &DownloadOptions) -> Result<(), WhisperStreamError> {
+    let Some(coreml_base_name) = published_coreml_base_name(model) else {
+        return Ok(());
+    };
+    let coreml_model_dir_path = cache_dir.join(format!("{}-encoder.mlmodelc", coreml_base_name));
+    if tokio::fs::try_exists(&coreml_model_dir_path).await.map_err(WhisperStreamError::from)? {
+        info!("CoreML model already present at {}.", coreml_model_dir_path.display());
+        return Ok(());
+    }
+    ensure_online(options, model, &coreml_model_dir_path)?;
+
+    let coreml_model_zip_url = COREML_MODEL_URL_TEMPLATE.replace("{}", coreml_base_name);
+    let coreml_zip_path = cache_dir.join(format!("{}-encoder.mlmodelc.zip", coreml_base_name));
+    info!("Downloading CoreML model from {} to {}...", coreml_model_zip_url, coreml_zip_path.display());
+    download_file_async(&coreml_model_zip_url, &coreml_zip_path, None, options).await?;
+
+    let cache_dir = cache_dir.to_path_buf();
+    let progress = options.extract_progress.clone();
+    spawn_blocking(move || extract_coreml_zip(&coreml_zip_path, &cache_dir, &coreml_model_dir_path, progress.as_ref())).await
+}
+
+/// Magic number at the start of every ggml model file ("ggml" as a little-endian u32).
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+/// Size of the ggml header: magic followed by eleven i32 hyperparameters, the last being `ftype`.
+const GGML_HEADER_LEN: usize = 4 + 11 * 4;
+
+/// Loads a Whisper context from `model_path`.
+///
+/// If whisper-rs fails to load the file, the failure is diagnosed and returned as a
+/// [`WhisperStreamError::ModelLoad`] with an actionable message instead of an opaque init error.
+pub(crate) fn load_context(model_path: &Path) -> Result<WhisperContext, WhisperStreamError> {
+    let path_str = model_path.to_str()
+        .ok_or_else(|| WhisperStreamError::ModelLoad(format!("Model path is not valid UTF-8: {}", model_path.display())))?;
+    WhisperContext::new_with_params(path_str, context_params())
+        .map_err(|e| WhisperStreamError::ModelLoad(format!("{} ({})", diagnose_model_load_failure(model_path), e)))
+}
+
+/// Downloads `model` if needed and loads it into a ready-to-use [`WhisperContext`].
+///
+/// The context is created with the same parameters the stream uses: the GPU is enabled when
+/// the `metal` feature is on, and with the `coreml` feature the CoreML encoder fetched by
+/// [`ensure_model`] is picked up from next to the model file. Failures to initialize the
+/// context are returned as [`WhisperStreamError::ContextInit`].
+pub fn load_whisper_context(model: Model) -> Result<WhisperContext, WhisperStreamError> {
+    init_context(&ensure_model(model)?)
+}
+
+fn init_context(model_path: &Path) -> Result<WhisperContext, WhisperStreamError> {
+    load_context(model_path).map_err(|e| match e {
+        WhisperStreamError::ModelLoad(message) => WhisperStreamError::ContextInit(message),
+        other => other,
+    })
+}
+
+/// Returns the Whisper context parameters for the enabled acceleration features.
+fn context_params() -> WhisperContextParameters<'static> {
+    let mut params = WhisperContextParameters::default();
+    if cfg!(feature = "metal") {
+        params.use_gpu(true);
+    }
+    params
+}
+
+/// Loads the first model of `models` that can be loaded, falling back on resource failures.
+///
+/// A load failure of a model file with a valid ggml header (full precision or quantized) most
+/// likely means whisper.cpp could not allocate the model's buffers, so the next (presumably
+/// smaller) model is tried with a warning. Failures caused by a truncated or non-ggml file are
+/// returned immediately as [`WhisperStreamError::ModelLoad`]. If every model fails this way,
+/// [`WhisperStreamError::OutOfMemory`] is returned.
+///
+/// Models are only downloaded when they are tried. Note that some allocation failures abort
+/// the process inside whisper.cpp and cannot be recovered from.
+#[cfg(feature = "microphone")]
+pub(crate) fn load_context_with_fallback(models: &[Model]) -> Result<(Model, WhisperContext), WhisperStreamError> {
+    load_with_fallback(models, ensure_model, load_context)
+}
+
+/// [`load_context_with_fallback`] with the download and load steps passed in.
+#[cfg(feature = "microphone")]
+fn load_with_fallback<T>(
+    models: &[Model],
+    mut ensure: impl FnMut(Model) -> Result<PathBuf, WhisperStreamError>,
+    mut load: impl FnMut(&Path) -> Result<T, WhisperStreamError>,
+) -> Result<(Model, T), WhisperStreamError> {
+    let mut failures = Vec::new();
+    for (index, &model) in models.iter().enumerate() {
+        let model_path = ensure(model)?;
+        match load(&model_path) {
+            Ok(ctx) => return Ok((model, ctx)),
+            Err(e) if read_ggml_header(&model_path).is_ok() => {
+                if let Some(next) = models.get(index + 1) {
+                    warn!("Could not load model {} (likely out of memory); falling back to {}.", model, next);
+                }
+                failures.push(format!("{}: {}", model, e));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(WhisperStreamError::OutOfMemory(if failures.is_empty() {
+        "no models to load".to_string()
+    } else {
+        failures.join("; ")
+    }))
+}
+
+/// Inspects a model file that failed to load and explains the most likely cause.
+fn diagnose_model_load_failure(model_path: &Path) -> String {
+    inspect_model_file(model_path).unwrap_or_else(|| format!(
+        "Model file {} has a valid ggml header but could not be loaded; the system may be out of memory, or the file may be truncated or corrupted (delete it so it is downloaded again).",
+        model_path.display()
+    ))
+}
+
+/// Checks a model file for problems that prevent loading, returning `None` if it looks intact.
+fn inspect_model_file(model_path: &Path) -> Option<String> {
+    let header = match read_ggml_header(model_path) {
+        Ok(header) => header,
+        Err(problem) => return Some(problem),
+    };
+
+    let ftype_offset = GGML_HEADER_LEN - 4;
+    let ftype = i32::from_le_bytes([header[ftype_offset], header[ftype_offset + 1], header[ftype_offset + 2], header[ftype_offset + 3]]);
+    // The quantization version is stored in the thousands; ftype 0 is f32, 1 is f16, and the
+    // rest are the quantizations whisper.cpp can load.
+    if !matches!(ftype % 1000, 0..=3 | 7..=14) {
+        return Some(format!(
+            "Model file {} uses a quantization (ftype {}) that whisper.cpp does not support. Use a full-precision model or one quantized as q4_0, q4_1, q5_0, q5_1, q8_0 or a k-quant.",
+            model_path.display(), ftype
+        ));
+    }
+
+    #[cfg(feature = "coreml")]
+    {
+        let stem = model_path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        let coreml_dir = model_path.with_file_name(format!("{}-encoder.mlmodelc", coreml_base_name(stem)));
+        if coreml_dir.exists() {
+            return Some(format!(
+                "Model file {} looks valid, but the CoreML encoder at {} may be incompatible with it. Delete the encoder directory so it is fetched again.",
+                model_path.display(), coreml_dir.display()
+            ));
+        }
+    }
+
+    None
+}
+
+/// Reads the ggml header of `model_path`, explaining why if it can't be read or isn't ggml.
+fn read_ggml_header(model_path: &Path) -> Result<[u8; GGML_HEADER_LEN], String> {
+    let mut file = fs::File::open(model_path)
+        .map_err(|e| format!("Model file {} could not be opened: {}. Check the path and permissions.", model_path.display(), e))?;
+
+    let mut header = [0u8; GGML_HEADER_LEN];
+    if let Err(e) = io::Read::read_exact(&mut file, &mut header) {
+        return Err(format!(
+            "Model file {} is truncated ({}). Delete it so it is downloaded again.",
+            model_path.display(), e
+        ));
+    }
+
+    let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    if magic != GGML_MAGIC {
+        return Err(format!(
+            "Model file {} is not a ggml model (magic 0x{:08x}); it may be an HTML error page from a failed download. Delete it so it is downloaded again.",
+            model_path.display(), magic
+        ));
+    }
+    Ok(header)
+}
+
+/// A completed download.
+#[derive(Debug)]
+struct Fetched {
+    /// Lowercase hex SHA-256 digest of the file.
+    digest: String,
+    /// Validators the server sent with the file; always empty for a custom [`Downloader`].
+    validators: CacheValidators,
+}
+
+/// Downloads `url` to `path`, retrying transient failures as configured in `options`.
+///
+/// Returns the SHA-256 digest of the file, computed while the bytes are written so callers
+/// don't need a second pass over large models. A download that doesn't match
+/// `expected_sha256` is deleted and reported as [`WhisperStreamError::ChecksumMismatch`]
+/// before it ever reaches `path`.
+///
+/// Uses [`DownloadOptions::downloader`] if one is set, otherwise the built-in HTTP client.
+fn download_file(url: &str, path: &Path, expected_sha256: Option<&str>, options: &DownloadOptions) -> Result<Fetched, WhisperStreamError> {
+    let Some(downloader) = &options.downloader else {
+        return download_file_with_retry(url, path, expected_sha256, options, options.max_retries, options.retry_base_delay);
+    };
+    let part_path = part_path(path);
+    if is_cancelled(options) {
+        return Err(cancel_download(&part_path));
+    }
+    downloader.download(url, &part_path)?;
+    let digest = sha256_file(&part_path)?;
+    check_digest(expected_sha256, &part_path, &digest)?;
+    fs::rename(&part_path, path).map_err(WhisperStreamError::from)?;
+    Ok(Fetched { digest, validators: CacheValidators::default() })
+}
+
+/// Downloads `url` to `path`, retrying up to `max_retries` times on transient failures.
+///
+/// Connection errors, interrupted bodies and 5xx responses are retried after `base_delay`,
+/// doubling the delay each time; other failures such as a 404 are returned immediately.
+/// Retries resume from the `.part` file, so they only fetch the missing bytes.
+fn download_file_with_retry(url: &str, path: &Path, expected_sha256: Option<&str>, options: &DownloadOptions, max_retries: u32, base_delay: Duration) -> Result<Fetched, WhisperStreamError> {
+    let mut attempt = 0;
+    loop {
+        if is_cancelled(options) {
+            return Err(cancel_download(&part_path(path)));
+        }
+        match download_attempt(url, path, expected_sha256, options) {
+            Ok(fetched) => return Ok(fetched),
+            Err(failure) if failure.transient && attempt < max_retries => {
+                let delay = base_delay.saturating_mul(1 << attempt.min(16));
+                attempt += 1;
+                warn!("Download of {} failed ({}); retry {} of {} in {:?}.", url, failure.error, attempt, max_retries, delay);
+                thread::sleep(delay);
+            }
+            Err(failure) => return Err(failure.error),
+        }
+    }
+}
+
+fn is_cancelled(options: &DownloadOptions) -> bool {
+    options.cancellation_token.as_ref().is_some_and(CancellationToken::is_cancelled)
+}
+
+/// Deletes the partial download at `part_path` and returns [`WhisperStreamError::Cancelled`].
+fn cancel_download(part_path: &Path) -> WhisperStreamError {
+    info!("Download cancelled; removing {}.", part_path.display());
+    if let Err(e) = fs::remove_file(part_path)
+        && e.kind() != io::ErrorKind::NotFound
+    {
+        warn!("Failed to remove partial download {}: {}", part_path.display(), e);
+    }
+    WhisperStreamError::Cancelled
+}
+
+/// A failed download attempt and whether trying again might succeed.
+struct AttemptFailure {
+    error: WhisperStreamError,
+    transient: bool,
+}
+
+impl AttemptFailure {
+    fn transient(error: WhisperStreamError) -> Self {
+        Self { error, transient: true }
+    }
+
+    fn permanent(error: WhisperStreamError) -> Self {
+        Self { error, transient: false }
+    }
+}
+
+/// Downloads `url` to `path`, resuming an earlier interrupted attempt if possible.
+///
+/// Bytes are written to `<path>.part`, which is renamed to `path` only once the full length
+/// has arrived and matches `expected_sha256` (if given), so an interrupted or corrupt download
+/// never leaves a complete-looking file. If a `.part` file
+/// exists, only the missing range is requested; servers that ignore the `Range` header (and
+/// answer `200` instead of `206`) make the download restart from scratch.
+///
+/// Returns the SHA-256 digest of the complete file and the server's validators for it. When
+/// resuming, only the existing partial bytes are read back to seed the hash; the rest is
+/// hashed as it is written.
+fn download_attempt(url: &str, path: &Path, expected_sha256: Option<&str>, options: &DownloadOptions) -> Result<Fetched, AttemptFailure> {
+    let client = download_client(options).map_err(AttemptFailure::permanent)?;
+
+    let part_path = part_path(path);
+    let resume_from = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+    let resp = request.send()
+        .map_err(|e| {
+            let message = if e.is_timeout() {
+                format!("Download from {} timed out waiting for the server (connect timeout {:?}, read timeout {:?})", url, options.connect_timeout, options.read_timeout)
+            } else {
+                format!("Failed to initiate download from {}: {}", url, e)
+            };
+            AttemptFailure::transient(WhisperStreamError::ModelFetch(message))
+        })?;
+
+    if resume_from > 0 && resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is no prefix of the current remote file; start over.
+        warn!("Server rejected resuming {} at byte {}; restarting download.", url, resume_from);
+        fs::remove_file(&part_path).map_err(|e| AttemptFailure::permanent(e.into()))?;
+        return download_attempt(url, path, expected_sha256, options);
+    }
+    let status = resp.status();
+    if !status.is_success() {
+        let error = WhisperStreamError::ModelFetch(format!("Failed to download from {}: HTTP Status {}", url, status));
+        // Server errors, timeouts and rate limiting may clear up; other client errors won't.
+        let transient = status.is_server_error()
+            || status == reqwest::StatusCode::REQUEST_TIMEOUT
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        return Err(if transient { AttemptFailure::transient(error) } else { AttemptFailure::permanent(error) });
+    }
+
+    let start = if resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        info!("Resuming download of {} at byte {}.", url, resume_from);
+        resume_from
+    } else {
+        if resume_from > 0 {
+            info!("Server does not support resuming {}; restarting download.", url);
+        }
+        0
+    };
+    let expected_len = resp.content_length().map(|len| start + len);
+    let validators = CacheValidators::from_headers(resp.headers());
+    let mut hasher = Sha256::new();
+    let file = if start > 0 {
+        fs::File::open(&part_path)
+            .and_then(|mut existing| io::copy(&mut existing, &mut hasher))
+            .and_then(|_| fs::OpenOptions::new().append(true).open(&part_path))
+    } else {
+        fs::File::create(&part_path)
+    }.map_err(|e| AttemptFailure::permanent(WhisperStreamError::Io { source: e }))?;
+    let mut out = HashingWriter::with_hasher(file, hasher);
+
+    let mut body: Box<dyn Read> = match options.max_bytes_per_sec {
+        Some(limit) => Box::new(ThrottledReader::new(resp, limit)),
+        None => Box::new(resp),
+    };
+    if let Some(callback) = options.progress.clone() {
+        body = Box::new(ProgressReader { inner: body, callback, total: expected_len, bytes_read: start });
+    }
+    if let Some(token) = options.cancellation_token.clone() {
+        body = Box::new(CancellableReader { inner: body, token });
+    }
+    // Apart from cancellation, errors here are almost always the connection dropping mid-body.
+    let written = io::copy(&mut body, &mut out)
+        .map_err(|e| if is_cancelled(options) {
+            AttemptFailure::permanent(cancel_download(&part_path))
+        } else if e.kind() == io::ErrorKind::TimedOut {
+            AttemptFailure::transient(WhisperStreamError::ModelFetch(format!(
+                "Download from {} stalled for longer than the read timeout of {:?}", url, options.read_timeout
+            )))
+        } else {
+            AttemptFailure::transient(WhisperStreamError::Io { source: e })
+        })?;
+
+    out.flush().map_err(|e| AttemptFailure::permanent(WhisperStreamError::Io { source: e }))?;
+    let digest = out.finalize();
+    if let Some(expected_len) = expected_len
+        && start + written != expected_len
+    {
+        return Err(AttemptFailure::transient(WhisperStreamError::ModelFetch(format!(
+            "Download from {} was interrupted at {} of {} bytes; it resumes on the next attempt", url, start + written, expected_len
+        ))));
+    }
+    check_digest(expected_sha256, &part_path, &digest).map_err(AttemptFailure::permanent)?;
+    fs::rename(&part_path, path).map_err(|e| AttemptFailure::permanent(e.into()))?;
+    Ok(Fetched { digest, validators })
+}
+
+/// Writer adapter that feeds everything written through it into a SHA-256 hasher.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    #[cfg(test)]
+    fn new(inner: W) -> Self {
+        Self::with_hasher(inner, Sha256::new())
+    }
+
+    /// Continues hashing from `hasher`, e.g. one already fed the start of a resumed file.
+    fn with_hasher(inner: W, hasher: Sha256) -> Self {
+        Self { inner, hasher }
+    }
+
+    /// Drops the inner writer and returns the lowercase hex digest of the bytes written.
+    fn finalize(self) -> String {
+        hex_digest(self.hasher)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Builds the HTTP client used for downloads, applying the timeouts and proxy from `options`.
+pub(crate) fn download_client(options: &DownloadOptions) -> Result<reqwest::blocking::Client, WhisperStreamError> {
+    // The blocking client applies `timeout` to the initial response and to each body read
+    // separately, which makes it a stall timeout rather than a limit on the whole transfer.
+    let mut builder = reqwest::blocking::Client::builder()
+        .connect_timeout(options.connect_timeout)
+        .timeout(options.read_timeout);
+    // Without an explicit proxy, reqwest picks up the proxy environment variables itself.
+    if let Some(proxy) = &options.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| WhisperStreamError::ModelFetch(format!("Invalid proxy URL {}: {}", proxy, e)))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    builder.build()
+        .map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to create HTTP client: {}", e)))
+}
+
+/// Returns the path an in-progress download of `path` is written to.
+fn part_path(path: &Path) -> PathBuf {
+    append_to_file_name(path, ".part")
+}
+
+/// Reader adapter that sleeps as needed to keep the average read rate under a limit.
+struct ThrottledReader<R> {
+    inner: R,
+    bytes_per_sec: u64,
+    started: Instant,
+    bytes_read: u64,
+}
+
+impl<R: Read> ThrottledReader<R> {
+    fn new(inner: R, bytes_per_sec: u64) -> Self {
+        Self {
+            inner,
+            bytes_per_sec: bytes_per_sec.max(1),
+            started: Instant::now(),
+            bytes_read: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read at most ~100ms worth of data at a time so sleeps stay short and smooth.
+        let max_chunk = (self.bytes_per_sec / 10).max(1) as usize;
+        let len = buf.len().min(max_chunk);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.bytes_read += n as u64;
+
+        let expected = Duration::from_secs_f64(self.bytes_read as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.started.elapsed();
+        if expected > elapsed {
+            thread::sleep(expected - elapsed);
+        }
+        Ok(n)
+    }
+}
+
+/// Reader adapter that reports the running byte count after every read.
+struct ProgressReader<R> {
+    inner: R,
+    callback: ProgressCallback,
+    total: Option<u64>,
+    bytes_read: u64,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.bytes_read += n as u64;
+            (self.callback)(self.bytes_read, self.total);
+        }
+        Ok(n)
+    }
+}
+
+/// Reader adapter that fails as soon as `token` is cancelled, so downloads stop between chunks.
+struct CancellableReader<R> {
+    inner: R,
+    token: CancellationToken,
+}
+
+impl<R: Read> Read for CancellableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.token.is_cancelled() {
+            return Err(io::Error::other("download cancelled"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Extracts `zip_path` into `dest_dir`, reporting progress to `progress` as described for
+/// [`ExtractProgressCallback`].
+///
+/// Entries are only ever written inside `dest_dir`: entries with absolute or `..` paths are
+/// skipped, symlink entries are rejected, and each entry's resolved parent directory is
+/// checked against the canonicalized destination so an existing symlink can't redirect it.
+#[cfg(any(feature = "coreml", test))]
+fn unzip_file(zip_path: &Path, dest_dir: &Path, progress: Option<&dyn Fn(usize, usize)>) -> Result<(), WhisperStreamError> {
+    let file = File::open(zip_path).map_err(|e| WhisperStreamError::Io { source: e })?;
+    let mut archive = ZipArchive::new(file).map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to open zip archive '{}': {}", zip_path.display(), e)))?;
+    fs::create_dir_all(dest_dir).map_err(|e| WhisperStreamError::Io { source: e })?;
+    let dest_root = dest_dir.canonicalize().map_err(|e| WhisperStreamError::Io { source: e })?;
+    let escapes = |name: &str| WhisperStreamError::ModelFetch(format!(
+        "Zip entry '{}' in '{}' would be extracted outside {}", name, zip_path.display(), dest_dir.display()
+    ));
+
+    let total = archive.len();
+    let report = |done: usize| {
+        // Only when the whole percentage changes, so huge archives don't flood the callback.
+        if let Some(progress) = progress && (done == total || done * 100 / total != (done - 1) * 100 / total) {
+            progress(done, total);
+        }
+    };
+    for i in 0..total {
+        if i > 0 {
+            report(i);
+        }
+        let mut file_in_zip = archive.by_index(i).map_err(|e| WhisperStreamError::ModelFetch(format!("Failed to access file in zip '{}': {}", zip_path.display(), e)))?;
+        let outpath = match file_in_zip.enclosed_name() {
+            Some(path) => dest_dir.join(path),
+            None => {
+                warn!("Skipping zip entry with unsafe path '{}' in {}", file_in_zip.name(), zip_path.display());
+                continue;
+            }
+        };
+        if file_in_zip.unix_mode().is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            return Err(WhisperStreamError::ModelFetch(format!(
+                "Refusing to extract symlink entry '{}' from '{}'", file_in_zip.name(), zip_path.display()
+            )));
+        }
+
+        let dir = if file_in_zip.is_dir() { outpath.as_path() } else { outpath.parent().unwrap_or(dest_dir) };
+        fs::create_dir_all(dir).map_err(|e| WhisperStreamError::Io { source: e })?;
+        let resolved = dir.canonicalize().map_err(|e| WhisperStreamError::Io { source: e })?;
+        if !resolved.starts_with(&dest_root) {
+            return Err(escapes(file_in_zip.name()));
+        }
+        if file_in_zip.is_dir() {
+            continue;
+        }
+        if fs::symlink_metadata(&outpath).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(escapes(file_in_zip.name()));
+        }
+        let mut outfile = fs::File::create(&outpath).map_err(|e| WhisperStreamError::Io { source: e })?;
+        io::copy(&mut file_in_zip, &mut outfile).map_err(|e| WhisperStreamError::Io { source: e })?;
+    }
+    if total > 0 {
+        report(total);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_from_str_round_trips() {
+        for model in Model::list() {
+            assert_eq!(model.name().parse::<Model>(), Ok(model));
+            assert_eq!(model.to_string().parse::<Model>(), Ok(model));
+            assert!(model.url().ends_with(model.file_name()));
+        }
+        assert_eq!("medium.en".parse::<Model>(), Ok(Model::MediumEn));
+        assert_eq!("large-v3".parse::<Model>(), Ok(Model::LargeV3));
+        assert_eq!(Model::LargeV3.file_name(), "ggml-large-v3.bin");
+        assert!("large".parse::<Model>().is_err());
+    }
+
+    #[test]
+    fn test_model_is_multilingual() {
+        for model in Model::list() {
+            assert_eq!(model.is_multilingual(), !model.name().split('-').next().unwrap().ends_with(".en"), "{}", model);
+        }
+        assert_eq!("base".parse::<Model>(), Ok(Model::Base));
+        assert_eq!(Model::Small.file_name(), "ggml-small.bin");
+    }
+
+    #[test]
+    fn test_quantized_models() {
+        assert_eq!("base.en-q5_1".parse::<Model>(), Ok(Model::BaseEnQ5_1));
+        assert_eq!(Model::BaseEnQ5_1.file_name(), "ggml-base.en-q5_1.bin");
+        assert_eq!(Model::BaseEnQ5_1.quantization(), Some("q5_1"));
+        assert_eq!(Model::LargeV3Q5_0.quantization(), Some("q5_0"));
+        assert_eq!(Model::LargeV3.quantization(), None);
+        assert_eq!(Model::BaseEn.quantization(), None);
+        assert_eq!(Model::list().iter().filter(|m| m.quantization().is_some()).count(), 9);
+    }
+
+    #[test]
+    fn test_model_download_url_uses_mirror() {
+        for model in Model::list() {
+            assert_eq!(model.url_with_base(DEFAULT_MODEL_BASE_URL), model.url());
+        }
+        let options = DownloadOptions { mirror: Some("https://mirror.example/whisper/".to_string()), ..DownloadOptions::default() };
+        assert_eq!(model_download_url(Model::BaseEn, &options).unwrap(), "https://mirror.example/whisper/ggml-base.en.bin");
+
+        for invalid in ["not a url", "ftp://mirror.example/whisper", "mirror.example/whisper"] {
+            let options = DownloadOptions { mirror: Some(invalid.to_string()), ..DownloadOptions::default() };
+            assert!(matches!(model_download_url(Model::BaseEn, &options), Err(WhisperStreamError::ModelFetch(_))), "{}", invalid);
+        }
+    }
+
+    #[cfg(feature = "coreml")]
+    #[test]
+    fn test_coreml_base_name_per_model() {
+        assert_eq!(published_coreml_base_name(Model::TinyEn), Some("ggml-tiny.en"));
+        assert_eq!(published_coreml_base_name(Model::LargeV3), Some("ggml-large-v3"));
+        assert_eq!(published_coreml_base_name(Model::BaseEnQ5_1), Some("ggml-base.en"));
+        assert_eq!(published_coreml_base_name(Model::MediumEnQ8_0), Some("ggml-medium.en"));
+        assert_eq!(coreml_base_name("ggml-custom-finetune"), "ggml-custom-finetune");
+        for model in Model::list() {
+            assert!(published_coreml_base_name(model).is_some(), "{}", model);
+        }
+    }
+
+    #[test]
+    fn test_from_file_name() {
+        for model in Model::list() {
+            assert_eq!(Model::from_file_name(model.file_name()), Some(model));
+        }
+        assert_eq!(Model::from_file_name("ggml-base.en.bin"), Some(Model::BaseEn));
+        assert_eq!(Model::from_file_name("base.en"), None);
+        assert_eq!(Model::from_file_name("ggml-base.en.bin.sha256"), None);
+        assert_eq!(Model::from_file_name("ggml-huge.bin"), None);
+        assert_eq!(Model::from_file_name(""), None);
+    }
+
+    #[test]
+    fn test_model_metadata_is_populated() {
+        for model in Model::list() {
+            assert!(model.download_size_bytes() > 0, "{}", model);
+            assert!(model.parameter_count() > 0, "{}", model);
+            assert!(model.approx_ram_mb() > 0, "{}", model);
+            // Running a model takes at least as much memory as its weights.
+            assert!(u64::from(model.approx_ram_mb()) * 1_000_000 > model.download_size_bytes(), "{}", model);
+        }
+        assert!(Model::SmallEnQ5_1.download_size_bytes() < Model::SmallEn.download_size_bytes());
+        assert_eq!(Model::SmallEnQ5_1.parameter_count(), Model::SmallEn.parameter_count());
+    }
+
+    #[test]
+    fn test_pinned_digests_are_sha256_hex() {
+        for (model, digest) in PINNED_SHA256 {
+            assert_eq!(digest.len(), 64, "{}", model);
+            assert!(digest.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')), "{}", model);
+            assert_eq!(PINNED_SHA256.iter().filter(|(m, _)| m == model).count(), 1, "{}", model);
+        }
+    }
+
+    #[test]
+    fn test_verify_cached_model_uses_recorded_checksum() {
+        let path = write_test_file("whisper-stream-rs-test-checksum.bin", b"model bytes");
+        // Nothing recorded, and shorter than the download: an interrupted download.
+        assert!(!verify_cached_model(Model::TinyEn, &path).unwrap());
+
+        fs::write(checksum_path(&path), sha256_file(&path).unwrap()).unwrap();
+        assert!(verify_cached_model(Model::TinyEn, &path).unwrap());
+
+        fs::write(&path, b"model by").unwrap();
+        assert!(!verify_cached_model(Model::TinyEn, &path).unwrap());
+
+        assert!(checksum_path(&path).to_str().unwrap().ends_with(".bin.sha256"));
+        let _ = fs::remove_file(checksum_path(&path));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_verify_cache_entry_matches_path() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-by-hash");
+        fs::create_dir_all(&dir).unwrap();
+        // SHA-256 of "abc"
+        let digest = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let entry = dir.join(digest);
+        fs::write(&entry, b"abc").unwrap();
+        assert!(verify_cache_entry(&entry).unwrap());
+
+        fs::write(&entry, b"abd").unwrap();
+        assert!(!verify_cache_entry(&entry).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn write_test_file(name: &str, bytes: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    /// Writes a fake cached model along with the digest a download would have recorded.
+    fn write_cached_model(path: &Path, bytes: &[u8]) {
+        fs::write(path, bytes).unwrap();
+        fs::write(checksum_path(path), sha256_file(path).unwrap()).unwrap();
+    }
+
+    fn ggml_header(ftype: i32) -> Vec<u8> {
+        let mut bytes = GGML_MAGIC.to_le_bytes().to_vec();
+        for _ in 0..10 {
+            bytes.extend_from_slice(&1i32.to_le_bytes());
+        }
+        bytes.extend_from_slice(&ftype.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_diagnose_model_load_failure() {
+        let missing = std::env::temp_dir().join("whisper-stream-rs-test-missing.bin");
+        assert!(diagnose_model_load_failure(&missing).contains("could not be opened"));
+
+        let truncated = write_test_file("whisper-stream-rs-test-truncated.bin", &GGML_MAGIC.to_le_bytes());
+        assert!(diagnose_model_load_failure(&truncated).contains("truncated"));
+
+        let html = write_test_file("whisper-stream-rs-test-html.bin", &[b'<'; GGML_HEADER_LEN]);
+        assert!(diagnose_model_load_failure(&html).contains("not a ggml model"));
+
+        // Q4_2 was dropped from ggml.
+        let unsupported = write_test_file("whisper-stream-rs-test-q4_2.bin", &ggml_header(5));
+        assert!(diagnose_model_load_failure(&unsupported).contains("does not support"));
+
+        let quantized = write_test_file("whisper-stream-rs-test-q5.bin", &ggml_header(1008));
+        assert!(inspect_model_file(&quantized).is_none());
+
+        let intact = write_test_file("whisper-stream-rs-test-f16.bin", &ggml_header(1));
+        assert!(inspect_model_file(&intact).is_none());
+        assert!(diagnose_model_load_failure(&intact).contains("out of memory"));
+
+        for path in [truncated, html, unsupported, quantized, intact] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[cfg(feature = "microphone")]
+    #[test]
+    fn test_load_with_fallback_falls_back_from_quantized_model() {
+        let large = write_test_file("whisper-stream-rs-test-fallback-large-q5_0.bin", &ggml_header(1008));
+        let base = write_test_file("whisper-stream-rs-test-fallback-base-q5_1.bin", &ggml_header(1009));
+        let path_of = |model| Ok(if model == Model::LargeV3Q5_0 { large.clone() } else { base.clone() });
+        let load = |path: &Path| if path == large {
+            Err(WhisperStreamError::ModelLoad("failed to allocate".to_string()))
+        } else {
+            Ok(path.to_path_buf())
+        };
+
+        let (model, loaded) = load_with_fallback(&[Model::LargeV3Q5_0, Model::BaseEnQ5_1], path_of, load).unwrap();
+        assert_eq!((model, loaded), (Model::BaseEnQ5_1, base.clone()));
+
+        let fail = |_: &Path| Err::<PathBuf, _>(WhisperStreamError::ModelLoad("failed to allocate".to_string()));
+        assert!(matches!(load_with_fallback(&[Model::LargeV3Q5_0, Model::BaseEnQ5_1], path_of, fail), Err(WhisperStreamError::OutOfMemory(_))));
+
+        // A broken file is reported as is rather than falling back.
+        fs::write(&large, b"<html>").unwrap();
+        assert!(matches!(load_with_fallback(&[Model::LargeV3Q5_0, Model::BaseEnQ5_1], path_of, load), Err(WhisperStreamError::ModelLoad(_))));
+
+        for path in [large, base] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_progress_reader_reports_running_total() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let mut reader = ProgressReader {
+            inner: io::Cursor::new(vec![1u8; 10]),
+            callback: Arc::new(move |done, total| sink.lock().unwrap().push((done, total))),
+            total: Some(10),
+            bytes_read: 0,
+        };
+        let mut buf = [0u8; 4];
+        while reader.read(&mut buf).unwrap() > 0 {}
+        assert_eq!(*reports.lock().unwrap(), vec![(4, Some(10)), (8, Some(10)), (10, Some(10))]);
+    }
+
+    #[test]
+    fn test_ensure_model_from_path() {
+        let quantized = write_test_file("whisper-stream-rs-test-custom-q5.bin", &ggml_header(8));
+        assert_eq!(ensure_model_from_path(&quantized).unwrap(), quantized);
+
+        let html = write_test_file("whisper-stream-rs-test-custom-html.bin", &[b'<'; GGML_HEADER_LEN]);
+        assert!(matches!(ensure_model_from_path(&html), Err(WhisperStreamError::ModelLoad(_))));
+        assert!(ensure_model_from_path(std::env::temp_dir()).is_err());
+
+        for path in [quantized, html] {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_prepare_cache_dir_creates_nested_dirs() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-cache").join("nested");
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+        prepare_cache_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+
+        let file = write_test_file("whisper-stream-rs-test-cache-file", b"");
+        let err = prepare_cache_dir(&file.join("sub")).unwrap_err();
+        assert!(matches!(err, WhisperStreamError::CacheDirNotWritable { ref path, .. } if *path == file.join("sub")));
+        assert!(err.to_string().contains(CACHE_DIR_ENV));
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+        let _ = fs::remove_file(file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clear_model_cache_removes_only_managed_files() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-clear-cache");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("ggml-base.en-encoder.mlmodelc")).unwrap();
+        fs::create_dir_all(dir.join(BY_HASH_DIR_NAME)).unwrap();
+        fs::write(dir.join("ggml-base.en.bin"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("ggml-base.en.bin.sha256"), "abc").unwrap();
+        fs::write(dir.join("ggml-base.en-encoder.mlmodelc").join("weights"), vec![0u8; 20]).unwrap();
+        fs::write(dir.join("ggml-tiny.en.bin.part"), vec![0u8; 7]).unwrap();
+        fs::write(dir.join(BY_HASH_DIR_NAME).join("def"), vec![0u8; 50]).unwrap();
+        fs::hard_link(dir.join(BY_HASH_DIR_NAME).join("def"), dir.join("ggml-small.en.bin")).unwrap();
+        fs::write(dir.join("ggml-small.en.bin.sha256"), "def").unwrap();
+        fs::write(dir.join(DEFAULT_MODEL_FILE_NAME), "base.en").unwrap();
+        fs::write(dir.join("notes.txt"), "keep me").unwrap();
+
+        assert_eq!(remove_model_in(Model::TinyEn, &dir).unwrap(), 7);
+        assert_eq!(remove_model_in(Model::TinyEn, &dir).unwrap(), 0);
+        // 100 + 3 + 20 + 50 + 3: the hard-linked model is only counted once.
+        assert_eq!(clear_model_cache_in(&dir).unwrap(), 176);
+
+        let mut remaining: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        remaining.sort();
+        assert_eq!(remaining, [DEFAULT_MODEL_FILE_NAME, "notes.txt"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_list_cached_models() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-list-cached");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("ggml-base.en-encoder.mlmodelc")).unwrap();
+        fs::write(dir.join("ggml-tiny.en.bin"), b"fake").unwrap();
+        fs::write(dir.join("ggml-base.en-q5_1.bin"), b"fake").unwrap();
+        fs::write(dir.join("ggml-small.en.bin.part"), b"fake").unwrap();
+
+        let cached = list_cached_models_in(&dir);
+        assert_eq!(cached, [
+            CachedModel { model: Model::TinyEn, path: dir.join("ggml-tiny.en.bin"), coreml_encoder: false },
+            CachedModel { model: Model::BaseEnQ5_1, path: dir.join("ggml-base.en-q5_1.bin"), coreml_encoder: true },
+        ]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ensure_model_check_only_never_downloads() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-check-only");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        assert_eq!(ensure_model_check_only_in(Model::TinyEn, &dir).unwrap(), None);
+        assert!(!dir.join(Model::TinyEn.file_name()).exists());
+
+        let path = dir.join(Model::TinyEn.file_name());
+        write_cached_model(&path, b"fake");
+        assert_eq!(ensure_model_check_only_in(Model::TinyEn, &dir).unwrap(), Some(path.clone()));
+
+        // A file that no longer matches its recorded checksum would be downloaded again.
+        fs::write(checksum_path(&path), "0".repeat(64)).unwrap();
+        assert_eq!(ensure_model_check_only_in(Model::TinyEn, &dir).unwrap(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ensure_models_keeps_order_and_skips_cached() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-ensure-models");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for model in [Model::TinyEn, Model::BaseEn, Model::SmallEn] {
+            write_cached_model(&dir.join(model.file_name()), b"fake");
+        }
+        // Nothing may be fetched: every request would fail against this server.
+        let (mirror, requests) = serve_status("404 Not Found");
+        let options = DownloadOptions { cache_dir: Some(dir.clone()), mirror: Some(mirror), ..DownloadOptions::default() };
+
+        let models = [Model::SmallEn, Model::TinyEn, Model::BaseEn, Model::TinyEn];
+        let paths = ensure_models_with_options(&models, &options).unwrap();
+        let expected: Vec<PathBuf> = models.iter().map(|m| dir.join(m.file_name())).collect();
+        assert_eq!(paths, expected);
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ensure_models_reports_every_failure() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-ensure-models-failing");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_cached_model(&dir.join(Model::TinyEn.file_name()), b"fake");
+        let (mirror, _) = serve_status("404 Not Found");
+        let options = DownloadOptions { cache_dir: Some(dir.clone()), mirror: Some(mirror), ..DownloadOptions::default() };
+
+        match ensure_models_with_options(&[Model::TinyEnQ5_1, Model::TinyEn, Model::BaseEnQ5_1], &options) {
+            Err(WhisperStreamError::ModelFetch(message)) => {
+                assert!(message.starts_with("2 of 3 model downloads failed"), "{}", message);
+                assert!(message.contains("tiny.en-q5_1") && message.contains("base.en-q5_1"), "{}", message);
+            }
+            other => panic!("expected a combined ModelFetch error, got {:?}", other),
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prepare_cache_dir_rejects_read_only_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-read-only-cache");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Privileged users bypass permission bits; there is nothing to test then.
+        let writable = fs::File::create(dir.join("probe")).is_ok();
+        let result = prepare_cache_dir(&dir);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        if !writable {
+            assert!(matches!(result, Err(WhisperStreamError::CacheDirNotWritable { .. })));
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// Writes a zip at `path` with the given `(name, contents)` files and `(name, target)` symlinks.
+    fn write_test_zip(path: &Path, files: &[(&str, &[u8])], symlinks: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in files {
+            zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        for (name, target) in symlinks {
+            zip.add_symlink(*name, *target, zip::write::FileOptions::default()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_unzip_file_reports_progress_per_entry() {
+        let root = std::env::temp_dir().join("whisper-stream-rs-test-unzip-progress");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let calls = std::sync::Mutex::new(Vec::new());
+        let record = |done: usize, total: usize| calls.lock().unwrap().push((done, total));
+
+        // Skipped entries still count towards progress.
+        let zip_path = root.join("small.zip");
+        write_test_zip(&zip_path, &[("a.bin", b"a"), ("dir/b.bin", b"b"), ("../skipped.bin", b"c")], &[]);
+        unzip_file(&zip_path, &root.join("small"), Some(&record)).unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec![(1, 3), (2, 3), (3, 3)]);
+
+        // A large archive is reported at most once per percent.
+        calls.lock().unwrap().clear();
+        let names: Vec<String> = (0..250).map(|i| format!("weights/{}.bin", i)).collect();
+        let files: Vec<(&str, &[u8])> = names.iter().map(|name| (name.as_str(), &b"x"[..])).collect();
+        let zip_path = root.join("large.zip");
+        write_test_zip(&zip_path, &files, &[]);
+        unzip_file(&zip_path, &root.join("large"), Some(&record)).unwrap();
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls.len(), 100);
+        assert!(calls.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(calls.last(), Some(&(250, 250)));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_extract_coreml_zip_rejects_incomplete_model() {
+        let root = std::env::temp_dir().join("whisper-stream-rs-test-coreml-extract");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let model_dir = root.join("ggml-tiny.en-encoder.mlmodelc");
+
+        let zip_path = root.join("incomplete.zip");
+        write_test_zip(&zip_path, &[("ggml-tiny.en-encoder.mlmodelc/coremldata.bin", b"data")], &[]);
+        match extract_coreml_zip(&zip_path, &root, &model_dir, None) {
+            Err(WhisperStreamError::ModelFetch(message)) => assert!(message.contains("model.mil"), "{}", message),
+            other => panic!("expected ModelFetch, got {:?}", other),
+        }
+        assert!(!model_dir.exists());
+        assert!(!zip_path.exists());
+
+        let zip_path = root.join("complete.zip");
+        write_test_zip(&zip_path, &[
+            ("ggml-tiny.en-encoder.mlmodelc/coremldata.bin", b"data"),
+            ("ggml-tiny.en-encoder.mlmodelc/model.mil", b"program"),
+        ], &[]);
+        extract_coreml_zip(&zip_path, &root, &model_dir, None).unwrap();
+        assert!(model_dir.join("model.mil").is_file());
+        assert!(!zip_path.exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_unzip_file_stays_inside_destination() {
+        let root = std::env::temp_dir().join("whisper-stream-rs-test-unzip");
+        let _ = fs::remove_dir_all(&root);
+        let dest = root.join("dest");
+        fs::create_dir_all(&root).unwrap();
+
+        // Traversal entries are skipped, the rest is extracted.
+        let zip_path = root.join("traversal.zip");
+        write_test_zip(&zip_path, &[("../escape.txt", b"evil"), ("/abs.txt", b"evil"), ("ok/file.txt", b"fine")], &[]);
+        unzip_file(&zip_path, &dest, None).unwrap();
+        assert_eq!(fs::read(dest.join("ok/file.txt")).unwrap(), b"fine");
+        assert!(!root.join("escape.txt").exists());
+
+        // Symlink entries are rejected outright.
+        let zip_path = root.join("symlink.zip");
+        write_test_zip(&zip_path, &[], &[("link", "/etc")]);
+        assert!(matches!(unzip_file(&zip_path, &dest, None), Err(WhisperStreamError::ModelFetch(_))));
+        assert!(fs::symlink_metadata(dest.join("link")).is_err());
+
+        // An existing symlink in the destination can't redirect an entry outside it.
+        #[cfg(unix)]
+        {
+            let outside = root.join("outside");
+            fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, dest.join("redirect")).unwrap();
+            let zip_path = root.join("redirect.zip");
+            write_test_zip(&zip_path, &[("redirect/owned.txt", b"evil")], &[]);
+            assert!(matches!(unzip_file(&zip_path, &dest, None), Err(WhisperStreamError::ModelFetch(_))));
+            assert!(!outside.join("owned.txt").exists());
+        }
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    /// Serves `status` with an empty body to every connection, counting the requests.
+    fn serve_status(status: &'static str) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            }
+        });
+        (url, requests)
+    }
+
+    /// Downloader that writes fixed bytes and records the URLs it was asked for.
+    struct FakeDownloader {
+        bytes: &'static [u8],
+        urls: std::sync::Mutex<Vec<String>>,
+        /// Answer to every [`Downloader::check_modified`].
+        freshness: Freshness,
+    }
+
+    impl Downloader for FakeDownloader {
+        fn download(&self, url: &str, path: &Path) -> Result<(), WhisperStreamError> {
+            self.urls.lock().unwrap().push(url.to_string());
+            fs::write(path, self.bytes).map_err(WhisperStreamError::from)
+        }
+
+        fn check_modified(&self, _url: &str, _cached: &CacheValidators) -> Result<Freshness, WhisperStreamError> {
+            Ok(self.freshness)
+        }
+    }
+
+    #[test]
+    fn test_ensure_model_uses_injected_downloader() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-downloader");
+        let _ = fs::remove_dir_all(&dir);
+        let downloader = Arc::new(FakeDownloader { bytes: b"fixture model", urls: Default::default(), freshness: Freshness::Unknown });
+        let options = DownloadOptions {
+            cache_dir: Some(dir.clone()),
+            mirror: Some("https://mirror.example/whisper".to_string()),
+            downloader: Some(downloader.clone()),
+            ..DownloadOptions::default()
+        };
+
+        let path = ensure_model_with_options(Model::TinyEn, &options).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"fixture model");
+        assert_eq!(fs::read_to_string(checksum_path(&path)).unwrap(), sha256_file(&path).unwrap());
+        assert!(!part_path(&path).exists());
+        // A second call finds the model cached and doesn't download again.
+        ensure_model_with_options(Model::TinyEn, &options).unwrap();
+        assert_eq!(*downloader.urls.lock().unwrap(), vec!["https://mirror.example/whisper/ggml-tiny.en.bin".to_string()]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_check_for_updates_replaces_only_modified_models() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-check-for-updates");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(Model::TinyEn.file_name());
+        write_cached_model(&path, b"old model");
+        let options = |freshness| {
+            let downloader = Arc::new(FakeDownloader { bytes: b"new model", urls: Default::default(), freshness });
+            (downloader.clone(), DownloadOptions {
+                cache_dir: Some(dir.clone()),
+                downloader: Some(downloader),
+                check_for_updates: true,
+                ..DownloadOptions::default()
+            })
+        };
+
+        // Like a 304 Not Modified: the cached model is kept.
+        let (downloader, unchanged) = options(Freshness::NotModified);
+        ensure_model_with_options(Model::TinyEn, &unchanged).unwrap();
+        assert!(downloader.urls.lock().unwrap().is_empty());
+        assert_eq!(fs::read(&path).unwrap(), b"old model");
+
+        // Without the option the server isn't asked at all.
+        let (downloader, mut changed) = options(Freshness::Modified);
+        changed.check_for_updates = false;
+        ensure_model_with_options(Model::TinyEn, &changed).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"old model");
+
+        changed.check_for_updates = true;
+        ensure_model_with_options(Model::TinyEn, &changed).unwrap();
+        assert_eq!(downloader.urls.lock().unwrap().len(), 1);
+        assert_eq!(fs::read(&path).unwrap(), b"new model");
+        assert_eq!(fs::read_to_string(checksum_path(&path)).unwrap(), sha256_file(&path).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cache_validators_round_trip_through_meta_file() {
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-meta.bin");
+        let validators = CacheValidators {
+            etag: Some("\"abc123\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+        };
+        write_cache_validators(&path, &validators).unwrap();
+        assert_eq!(read_cache_validators(&path), validators);
+        write_cache_validators(&path, &CacheValidators::default()).unwrap();
+        assert!(!meta_path(&path).exists());
+        assert!(read_cache_validators(&path).is_empty());
+    }
+
+    #[test]
+    fn test_http_check_modified() {
+        let cached = CacheValidators { etag: Some("\"v1\"".to_string()), last_modified: None };
+        let check = |response| HttpDownloader::default().check_modified(&serve_once(response), &cached).unwrap();
+
+        assert_eq!(check("HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n"), Freshness::NotModified);
+        // Servers ignoring If-None-Match answer 200; the ETag still tells whether it changed.
+        assert_eq!(check("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"), Freshness::NotModified);
+        assert_eq!(check("HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"), Freshness::Modified);
+        assert_eq!(check("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"), Freshness::Unknown);
+        // Nothing recorded at download: there is nothing to ask about.
+        assert_eq!(HttpDownloader::default().check_modified("http://127.0.0.1:9/model.bin", &CacheValidators::default()).unwrap(), Freshness::Unknown);
+    }
+
+    #[test]
+    fn test_download_records_cache_validators() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-validators.bin");
+        let fetched = download_file_with_retry(&url, &path, None, &DownloadOptions::default(), 0, Duration::ZERO).unwrap();
+        assert_eq!(fetched.validators, CacheValidators { etag: Some("\"v1\"".to_string()), last_modified: None });
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_model_identity_tells_models_apart() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-model-identity");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let stock = dir.join("ggml-base.en.bin");
+        let fine_tuned = dir.join("fine-tuned.bin");
+        fs::write(&stock, b"stock").unwrap();
+        fs::write(checksum_path(&stock), "aa".repeat(32)).unwrap();
+        fs::write(&fine_tuned, b"tuned").unwrap();
+
+        assert_eq!(model_identity(&stock).unwrap(), format!("sha256:{}", "aa".repeat(32)));
+        let tuned_id = model_identity(&fine_tuned).unwrap();
+        assert!(tuned_id.starts_with("file:") && tuned_id.contains("fine-tuned.bin"), "{}", tuned_id);
+        // An updated download records a new digest, and with it a new identity.
+        fs::write(checksum_path(&stock), "bb".repeat(32)).unwrap();
+        assert_eq!(model_identity(&stock).unwrap(), format!("sha256:{}", "bb".repeat(32)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prefetch_all_fetches_every_model() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-prefetch-all");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_cached_model(&dir.join(Model::BaseEn.file_name()), b"already cached");
+        let downloader = Arc::new(FakeDownloader { bytes: b"fixture model", urls: Default::default(), freshness: Freshness::Unknown });
+        let options = DownloadOptions {
+            cache_dir: Some(dir.clone()),
+            downloader: Some(downloader.clone()),
+            ..DownloadOptions::default()
+        };
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = reports.clone();
+
+        let report = prefetch_all_with_options(&options, move |done, total| recorded.lock().unwrap().push((done, total))).unwrap();
+        assert!(report.is_complete());
+        assert_eq!(report.ready.iter().map(|(model, _)| *model).collect::<Vec<_>>(), Model::all());
+        for (model, path) in &report.ready {
+            assert!(path.is_file(), "{}", model);
+        }
+        // The cached model is kept; every other one is downloaded once.
+        assert_eq!(fs::read(dir.join(Model::BaseEn.file_name())).unwrap(), b"already cached");
+        assert_eq!(downloader.urls.lock().unwrap().len(), Model::all().len() - 1);
+
+        let reports = reports.lock().unwrap();
+        let total: u64 = Model::all().iter().filter(|m| **m != Model::BaseEn).map(Model::download_size_bytes).sum();
+        assert_eq!(reports.len(), Model::all().len() - 1);
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(reports.last(), Some(&(total, total)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_prefetch_all_continues_past_failures() {
+        /// Fails for one model file and writes fixture bytes for the rest.
+        struct FlakyDownloader;
+        impl Downloader for FlakyDownloader {
+            fn download(&self, url: &str, path: &Path) -> Result<(), WhisperStreamError> {
+                if url.ends_with(Model::Small.file_name()) {
+                    return Err(WhisperStreamError::ModelFetch(format!("Failed to download from {}: HTTP Status 404 Not Found", url)));
+                }
+                fs::write(path, b"fixture model").map_err(WhisperStreamError::from)
+            }
+        }
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-prefetch-failures");
+        let _ = fs::remove_dir_all(&dir);
+        let options = DownloadOptions {
+            cache_dir: Some(dir.clone()),
+            downloader: Some(Arc::new(FlakyDownloader)),
+            ..DownloadOptions::default()
+        };
+
+        let report = prefetch_all_with_options(&options, |_, _| {}).unwrap();
+        assert!(!report.is_complete());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, Model::Small);
+        assert!(matches!(report.failed[0].1, WhisperStreamError::ModelFetch(_)));
+        assert_eq!(report.paths().len(), Model::all().len() - 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_injected_download_is_verified_before_it_lands() {
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-downloader-checksum.bin");
+        let _ = fs::remove_file(&path);
+        let options = DownloadOptions {
+            downloader: Some(Arc::new(FakeDownloader { bytes: b"tampered", urls: Default::default(), freshness: Freshness::Unknown })),
+            ..DownloadOptions::default()
+        };
+        let expected = "0".repeat(64);
+        assert!(matches!(
+            download_file("https://example.invalid/model.bin", &path, Some(&expected), &options),
+            Err(WhisperStreamError::ChecksumMismatch { .. })
+        ));
+        assert!(!path.exists());
+        assert!(!part_path(&path).exists());
+    }
+
+    #[test]
+    fn test_offline_never_contacts_the_mirror() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-offline");
+        let _ = fs::remove_dir_all(&dir);
+        let (url, requests) = serve_status("404 Not Found");
+        let options = DownloadOptions {
+            cache_dir: Some(dir.clone()),
+            mirror: Some(url.trim_end_matches("/model.bin").to_string()),
+            offline: true,
+            ..DownloadOptions::default()
+        };
+
+        match ensure_model_with_options(Model::TinyEn, &options) {
+            Err(WhisperStreamError::ModelNotCached { model, path }) => {
+                assert_eq!(model, Model::TinyEn.to_string());
+                assert_eq!(path, dir.join(Model::TinyEn.file_name()));
+            }
+            other => panic!("expected ModelNotCached, got {:?}", other),
+        }
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        // The same options online do reach the mirror.
+        let online = DownloadOptions { offline: false, max_retries: 0, ..options };
+        assert!(ensure_model_with_options(Model::TinyEn, &online).is_err());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_download_retries_server_errors_but_not_client_errors() {
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-retry.bin");
+        let options = DownloadOptions::default();
+
+        let (url, requests) = serve_status("503 Service Unavailable");
+        assert!(download_file_with_retry(&url, &path, None, &options, 2, Duration::from_millis(1)).is_err());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+        let (url, requests) = serve_status("404 Not Found");
+        assert!(download_file_with_retry(&url, &path, None, &options, 2, Duration::from_millis(1)).is_err());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_download_times_out_on_unresponsive_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        // Accept the connection but never answer.
+        thread::spawn(move || {
+            let _held: Vec<_> = listener.incoming().take(1).collect();
+            thread::sleep(Duration::from_secs(5));
+        });
+
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-timeout.bin");
+        let options = DownloadOptions { read_timeout: Some(Duration::from_millis(200)), ..DownloadOptions::default() };
+        match download_file_with_retry(&url, &path, None, &options, 0, Duration::ZERO) {
+            Err(WhisperStreamError::ModelFetch(message)) => assert!(message.contains("timed out"), "{}", message),
+            other => panic!("expected a timeout error, got {:?}", other),
+        }
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_download_client_routes_through_proxy() {
+        let proxy = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", proxy.local_addr().unwrap());
+        let request_line = thread::spawn(move || {
+            let (mut stream, _) = proxy.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let n = stream.read(&mut buf).unwrap();
+            let _ = write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            String::from_utf8_lossy(&buf[..n]).lines().next().unwrap_or_default().to_string()
+        });
+
+        let options = DownloadOptions { proxy: Some(proxy_url), ..DownloadOptions::default() };
+        let client = download_client(&options).unwrap();
+        let resp = client.get("http://models.invalid/model.bin").send().unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+        // A proxied request carries the absolute URL in its request line.
+        assert_eq!(request_line.join().unwrap(), "GET http://models.invalid/model.bin HTTP/1.1");
+    }
+
+    #[test]
+    fn test_download_client_rejects_invalid_proxy() {
+        let options = DownloadOptions { proxy: Some("not a url".to_string()), ..DownloadOptions::default() };
+        assert!(matches!(download_client(&options), Err(WhisperStreamError::ModelFetch(_))));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_download_file_async_writes_body() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+        });
+
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-async.bin");
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let digest = runtime.block_on(download_file_async(&url, &path, None, &DownloadOptions::default())).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+        assert_eq!(digest, sha256_file(&path).unwrap());
+        assert!(!part_path(&path).exists());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_ensure_space_rejects_downloads_that_do_not_fit() {
+        let needed = Model::TinyEnQ5_1.download_size_bytes();
+        assert!(ensure_space(needed, needed).is_ok());
+        assert!(ensure_space(needed, needed * 2).is_ok());
+        match ensure_space(needed, needed - 1) {
+            Err(WhisperStreamError::InsufficientSpace { needed: n, available: a }) => {
+                assert_eq!(n, needed);
+                assert_eq!(a, needed - 1);
+            }
+            other => panic!("expected InsufficientSpace, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_free_space_counts_resumed_bytes() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-free-space");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(Model::TinyEnQ5_1.file_name());
+        // A fully downloaded `.part` file leaves nothing to fetch, so any volume is big enough.
+        let part = fs::File::create(part_path(&path)).unwrap();
+        part.set_len(Model::TinyEnQ5_1.download_size_bytes()).unwrap();
+        assert!(check_free_space(Model::TinyEnQ5_1, &path).is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hashing_writer_hashes_and_forwards() {
+        let mut writer = HashingWriter::new(Vec::new());
+        io::copy(&mut io::Cursor::new(b"hello".to_vec()), &mut writer).unwrap();
+        assert_eq!(writer.inner, b"hello");
+        assert_eq!(writer.finalize(), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+    }
+
+    #[test]
+    fn test_download_file_returns_digest_of_resumed_file() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = write!(stream, "HTTP/1.1 206 Partial Content\r\nContent-Length: 3\r\nConnection: close\r\n\r\nllo");
+        });
+
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-resume-digest.bin");
+        fs::write(part_path(&path), b"he").unwrap();
+        let digest = download_file_with_retry(&url, &path, None, &DownloadOptions::default(), 0, Duration::ZERO).unwrap().digest;
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+        assert_eq!(digest, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+        let _ = fs::remove_file(&path);
+    }
+
+    /// Answers a single connection with the raw HTTP `response`.
+    fn serve_once(response: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(response.as_bytes());
+        });
+        url
+    }
+
+    #[test]
+    fn test_cancelled_download_removes_part_file() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/model.bin", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10000000\r\nConnection: close\r\n\r\n");
+            // Trickle the body until the client hangs up.
+            while stream.write_all(&[0u8; 16 * 1024]).is_ok() {
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-cancelled.bin");
+        let _ = fs::remove_file(&path);
+        let token = CancellationToken::new();
+        let cancel_on_first_chunk = token.clone();
+        let options = DownloadOptions {
+            // Like a user pressing stop as soon as the download starts.
+            progress: Some(Arc::new(move |_, _| cancel_on_first_chunk.cancel())),
+            cancellation_token: Some(token),
+            ..DownloadOptions::default()
+        };
+
+        assert!(matches!(download_file_with_retry(&url, &path, None, &options, 3, Duration::ZERO), Err(WhisperStreamError::Cancelled)));
+        assert!(!path.exists());
+        assert!(!part_path(&path).exists());
+        // An already cancelled token doesn't connect at all.
+        assert!(matches!(download_file("http://127.0.0.1:9/model.bin", &path, None, &options), Err(WhisperStreamError::Cancelled)));
+    }
+
+    #[test]
+    fn test_interrupted_download_stays_in_part_file() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\nhello");
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-interrupted.bin");
+        let _ = fs::remove_file(part_path(&path));
+        assert!(download_file_with_retry(&url, &path, None, &DownloadOptions::default(), 0, Duration::ZERO).is_err());
+        assert!(!path.exists());
+        assert_eq!(fs::read(part_path(&path)).unwrap(), b"hello");
+        let _ = fs::remove_file(part_path(&path));
+    }
+
+    #[test]
+    fn test_download_with_wrong_checksum_is_never_renamed() {
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-bad-checksum.bin");
+        let expected = "0".repeat(64);
+        let result = download_file_with_retry(&url, &path, Some(&expected), &DownloadOptions::default(), 0, Duration::ZERO);
+        assert!(matches!(result, Err(WhisperStreamError::ChecksumMismatch { .. })));
+        assert!(!path.exists());
+        assert!(!part_path(&path).exists());
+    }
+
+    #[test]
+    fn test_remove_stale_part_file_keeps_resumable_downloads() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-stale-part");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ggml-tiny.en.bin");
+        let _ = fs::remove_file(&path);
+
+        fs::write(part_path(&path), b"partial").unwrap();
+        remove_stale_part_file(&path).unwrap();
+        assert!(part_path(&path).exists());
+
+        fs::write(&path, b"complete").unwrap();
+        remove_stale_part_file(&path).unwrap();
+        assert!(!part_path(&path).exists());
+        assert!(path.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_part_path_appends_suffix() {
+        assert_eq!(part_path(Path::new("/cache/ggml-base.en.bin")), PathBuf::from("/cache/ggml-base.en.bin.part"));
+    }
+
+    #[test]
+    fn test_throttled_reader_limits_rate() {
+        let payload = vec![7u8; 2000];
+        let mut reader = ThrottledReader::new(io::Cursor::new(payload.clone()), 10_000);
+        let started = Instant::now();
+        let mut out = Vec::new();
+        io::copy(&mut reader, &mut out).unwrap();
+        assert_eq!(out, payload);
+        assert!(started.elapsed() >= Duration::from_millis(180));
+    }
+}
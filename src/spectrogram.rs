@@ -0,0 +1,130 @@
+//! Log-mel spectrogram rendering for debugging transcription problems.
+//!
+//! Enabled with the `spectrogram` feature. The output is meant for eyeballing whether and
+//! where speech energy is present; it follows Whisper's framing (25ms window, 10ms hop,
+//! 80 mel bands) but is not bit-exact with Whisper's own front end.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use rustfft::{FftPlanner, num_complex::Complex};
+use crate::error::WhisperStreamError;
+
+const SAMPLE_RATE: f32 = 16000.0;
+const WINDOW_LEN: usize = 400;
+const HOP_LEN: usize = 160;
+const FFT_LEN: usize = 512;
+const N_MELS: usize = 80;
+/// Dynamic range kept below the loudest bin, in log10 units (80dB).
+const DYNAMIC_RANGE: f32 = 8.0;
+
+/// Renders a log-mel spectrogram of 16kHz mono `samples` to a grayscale PNG at `path`.
+///
+/// Time runs left to right (one pixel per 10ms) and frequency bottom to top (one pixel
+/// per mel band); brighter pixels are louder.
+pub fn save_spectrogram<P: AsRef<Path>>(samples: &[f32], path: P) -> Result<(), WhisperStreamError> {
+    let frames = log_mel_frames(samples);
+    let width = frames.len().max(1);
+
+    let max = frames.iter().flatten().copied().fold(f32::NEG_INFINITY, f32::max);
+    let floor = max - DYNAMIC_RANGE;
+    let mut pixels = vec![0u8; width * N_MELS];
+    for (x, frame) in frames.iter().enumerate() {
+        for (mel, &value) in frame.iter().enumerate() {
+            let level = ((value - floor) / DYNAMIC_RANGE).clamp(0.0, 1.0);
+            let y = N_MELS - 1 - mel;
+            pixels[y * width + x] = (level * 255.0).round() as u8;
+        }
+    }
+
+    let file = File::create(path).map_err(WhisperStreamError::from)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, N_MELS as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()
+        .and_then(|mut writer| writer.write_image_data(&pixels))
+        .map_err(|e| WhisperStreamError::Internal(format!("Failed to encode spectrogram PNG: {}", e)))
+}
+
+/// Computes log10 mel energies for each 10ms frame of `samples`.
+fn log_mel_frames(samples: &[f32]) -> Vec<[f32; N_MELS]> {
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_LEN);
+    let window: Vec<f32> = (0..WINDOW_LEN)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / WINDOW_LEN as f32).cos())
+        .collect();
+    let filters = mel_filterbank();
+
+    let n_frames = samples.len().saturating_sub(WINDOW_LEN) / HOP_LEN + 1;
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); FFT_LEN];
+    let mut frames = Vec::with_capacity(n_frames);
+    for frame_idx in 0..n_frames {
+        let start = frame_idx * HOP_LEN;
+        buffer.iter_mut().for_each(|c| *c = Complex::new(0.0, 0.0));
+        for (i, w) in window.iter().enumerate() {
+            buffer[i].re = samples.get(start + i).copied().unwrap_or(0.0) * w;
+        }
+        fft.process(&mut buffer);
+
+        let mut mels = [0.0f32; N_MELS];
+        for (mel, filter) in filters.iter().enumerate() {
+            let energy: f32 = filter.iter()
+                .map(|&(bin, weight)| buffer[bin].norm_sqr() * weight)
+                .sum();
+            mels[mel] = energy.max(1e-10).log10();
+        }
+        frames.push(mels);
+    }
+    frames
+}
+
+/// Triangular mel filters as sparse `(fft_bin, weight)` lists.
+fn mel_filterbank() -> Vec<Vec<(usize, f32)>> {
+    let hz_to_mel = |hz: f32| 2595.0 * (1.0 + hz / 700.0).log10();
+    let mel_to_hz = |mel: f32| 700.0 * (10f32.powf(mel / 2595.0) - 1.0);
+    let max_mel = hz_to_mel(SAMPLE_RATE / 2.0);
+    let edges: Vec<f32> = (0..N_MELS + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (N_MELS + 1) as f32))
+        .collect();
+
+    let n_bins = FFT_LEN / 2 + 1;
+    (0..N_MELS)
+        .map(|m| {
+            let (lower, centre, upper) = (edges[m], edges[m + 1], edges[m + 2]);
+            (0..n_bins)
+                .filter_map(|bin| {
+                    let hz = bin as f32 * SAMPLE_RATE / FFT_LEN as f32;
+                    let weight = if hz > lower && hz <= centre {
+                        (hz - lower) / (centre - lower)
+                    } else if hz > centre && hz < upper {
+                        (upper - hz) / (upper - centre)
+                    } else {
+                        0.0
+                    };
+                    (weight > 0.0).then_some((bin, weight))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_spectrogram_writes_png_with_expected_size() {
+        // One second of a 1kHz tone.
+        let samples: Vec<f32> = (0..16000)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / SAMPLE_RATE).sin() * 0.5)
+            .collect();
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-spectrogram.png");
+        save_spectrogram(&samples, &path).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!(info.width as usize, (16000 - WINDOW_LEN) / HOP_LEN + 1);
+        assert_eq!(info.height as usize, N_MELS);
+        let _ = std::fs::remove_file(&path);
+    }
+}
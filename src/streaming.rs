@@ -0,0 +1,166 @@
+//! Incremental transcription of audio that arrives a chunk at a time.
+//!
+//! [`StreamingTranscriber`] keeps the audio that has not been committed yet, re-transcribes it
+//! on every poll and splits the result into committed text, which never changes again, and a
+//! pending suffix that later audio may still revise.
+
+use whisper_rs::WhisperContext;
+use crate::audio_utils::WHISPER_SAMPLE_RATE;
+use crate::error::WhisperStreamError;
+use crate::transcribe::{transcribe, TranscribeOptions};
+use crate::transcript::Segment;
+
+/// A segment is committed once it ends at least this long before the end of the buffered audio,
+/// so later audio can no longer change how it is heard.
+const COMMIT_DELAY_SECS: f64 = 1.0;
+
+/// Most uncommitted audio kept; beyond it everything but the last segment is committed, so
+/// memory and decode time stay bounded even when speech never pauses. Whisper's window is 30s.
+const MAX_BUFFER_SECS: f64 = 25.0;
+
+/// Transcribes a live stream by repeatedly decoding its uncommitted tail.
+///
+/// Feed audio with [`StreamingTranscriber::push`] and call [`StreamingTranscriber::poll`]
+/// whenever an updated transcript is wanted. Committed audio is dropped from the buffer.
+pub struct StreamingTranscriber<'a> {
+    ctx: &'a WhisperContext,
+    options: TranscribeOptions,
+    window: RollingWindow,
+}
+
+impl<'a> StreamingTranscriber<'a> {
+    /// Creates a transcriber that decodes with `ctx` and `options`.
+    pub fn new(ctx: &'a WhisperContext, options: TranscribeOptions) -> Self {
+        Self { ctx, options, window: RollingWindow::default() }
+    }
+
+    /// Appends 16kHz mono `samples` to the buffered audio.
+    pub fn push(&mut self, samples: &[f32]) {
+        self.window.samples.extend_from_slice(samples);
+    }
+
+    /// Transcribes the buffered audio and returns `(committed_text, pending_text)`.
+    ///
+    /// The committed text of a later poll always starts with the committed text of an earlier
+    /// one; the pending text is only a best guess for the audio after it.
+    pub fn poll(&mut self) -> Result<(String, String), WhisperStreamError> {
+        if self.window.samples.is_empty() {
+            return Ok((self.window.committed.clone(), String::new()));
+        }
+        let result = transcribe(self.ctx, &self.window.samples, &self.options)?;
+        Ok(self.window.advance(&result.segments))
+    }
+
+    /// Text committed so far.
+    pub fn committed_text(&self) -> &str {
+        &self.window.committed
+    }
+
+    /// Seconds of uncommitted audio currently buffered.
+    pub fn buffered_secs(&self) -> f64 {
+        self.window.buffered_secs()
+    }
+}
+
+/// Uncommitted audio and the text committed before it.
+#[derive(Debug, Default)]
+struct RollingWindow {
+    samples: Vec<f32>,
+    committed: String,
+}
+
+impl RollingWindow {
+    fn buffered_secs(&self) -> f64 {
+        self.samples.len() as f64 / WHISPER_SAMPLE_RATE as f64
+    }
+
+    /// Commits the stable prefix of `segments`, transcribed from the buffered audio, drops the
+    /// audio it covers and returns `(committed_text, pending_text)`.
+    fn advance(&mut self, segments: &[Segment]) -> (String, String) {
+        let buffered = self.buffered_secs();
+        let segments: Vec<&Segment> = segments.iter().filter(|s| !s.text.trim().is_empty()).collect();
+        let mut stable = segments.iter().take_while(|s| s.end <= buffered - COMMIT_DELAY_SECS).count();
+        if buffered > MAX_BUFFER_SECS {
+            stable = stable.max(segments.len().saturating_sub(1)).max(segments.len().min(1));
+        }
+
+        for segment in &segments[..stable] {
+            if !self.committed.is_empty() {
+                self.committed.push(' ');
+            }
+            self.committed.push_str(segment.text.trim());
+        }
+        let cut = match stable.checked_sub(1) {
+            Some(last) => segments[last].end.min(buffered),
+            // Nothing spoken in an overlong buffer: drop all but its most recent audio.
+            None if buffered > MAX_BUFFER_SECS => buffered - COMMIT_DELAY_SECS,
+            None => 0.0,
+        };
+        let cut_samples = ((cut * WHISPER_SAMPLE_RATE as f64) as usize).min(self.samples.len());
+        self.samples.drain(..cut_samples);
+
+        let pending = segments[stable..].iter()
+            .map(|s| s.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (self.committed.clone(), pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str) -> Segment {
+        Segment { start, end, text: text.to_string(), ..Segment::default() }
+    }
+
+    #[test]
+    fn test_rolling_window_commits_stable_prefix_and_trims_audio() {
+        let mut window = RollingWindow::default();
+        let mut previous = String::new();
+        // Each poll sees the buffer after one more second of audio, as whisper might hear it.
+        let polls: Vec<Vec<Segment>> = vec![
+            vec![segment(0.0, 1.0, " Hel")],
+            vec![segment(0.0, 1.5, " Hello there.")],
+            vec![segment(0.0, 1.5, " Hello there."), segment(1.5, 3.0, " How are")],
+            // "Hello there." was committed and its 1.5s trimmed; timestamps restart at 0.
+            vec![segment(0.0, 2.5, " How are you?")],
+            vec![segment(0.0, 2.5, " How are you?")],
+        ];
+        let mut results = Vec::new();
+        for segments in polls {
+            window.samples.extend(std::iter::repeat_n(0.0, WHISPER_SAMPLE_RATE as usize));
+            let (committed, pending) = window.advance(&segments);
+            assert!(committed.starts_with(&previous), "committed text shrank from {:?} to {:?}", previous, committed);
+            previous = committed.clone();
+            results.push((committed, pending));
+        }
+
+        let owned = |c: &str, p: &str| (c.to_string(), p.to_string());
+        assert_eq!(results, vec![
+            owned("", "Hel"),
+            owned("", "Hello there."),
+            owned("Hello there.", "How are"),
+            owned("Hello there.", "How are you?"),
+            owned("Hello there. How are you?", ""),
+        ]);
+        // 5s pushed, 1.5s + 2.5s committed.
+        assert!((window.buffered_secs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rolling_window_caps_buffered_audio() {
+        let mut window = RollingWindow { samples: vec![0.0; 30 * WHISPER_SAMPLE_RATE as usize], ..RollingWindow::default() };
+        // Speech that never pauses: nothing is stable, so all but the last segment is forced out.
+        let (committed, pending) = window.advance(&[segment(0.0, 29.5, " one"), segment(29.5, 30.0, " two")]);
+        assert_eq!((committed.as_str(), pending.as_str()), ("one", "two"));
+        assert!((window.buffered_secs() - 0.5).abs() < 1e-9);
+
+        // Silence is dropped too, keeping only the most recent audio.
+        window.samples = vec![0.0; 30 * WHISPER_SAMPLE_RATE as usize];
+        let (committed, pending) = window.advance(&[]);
+        assert_eq!((committed.as_str(), pending.as_str()), ("one", ""));
+        assert!((window.buffered_secs() - COMMIT_DELAY_SECS).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,145 @@
+//! Audio fixtures for downstream end-to-end tests.
+//!
+//! Enabled with the `testutil` feature. [`sample_clip`] is real speech with a known
+//! transcript, for tests that run the whole transcription path. [`synthetic_clip`] is
+//! synthesized, vowel-like bursts with known positions but no words, for testing capture,
+//! VAD and recording plumbing without depending on recognition.
+
+use std::f32::consts::PI;
+use std::ops::Range;
+
+/// Sample rate of [`sample_clip`] and [`synthetic_clip`].
+pub const SAMPLE_CLIP_SAMPLE_RATE: u32 = 16000;
+
+/// 11s of John F. Kennedy's 1961 inaugural address, 16kHz mono 16-bit PCM. A work of the US
+/// federal government, in the public domain; it is the `samples/jfk.wav` clip whisper.cpp
+/// tests with.
+const SAMPLE_CLIP_WAV: &[u8] = include_bytes!("../tests/fixtures/jfk.wav");
+
+/// What [`sample_clip`] says.
+const SAMPLE_CLIP_TRANSCRIPT: &str =
+    "And so my fellow Americans, ask not what your country can do for you, ask what you can do for your country.";
+
+/// Returns an 11s, 16kHz mono clip of English speech; see [`sample_clip_transcript`].
+pub fn sample_clip() -> Vec<f32> {
+    let reader = hound::WavReader::new(SAMPLE_CLIP_WAV).expect("bundled sample clip is a valid WAV");
+    reader.into_samples::<i16>()
+        .map(|s| s.expect("bundled sample clip is a valid WAV") as f32 / 32768.0)
+        .collect()
+}
+
+/// Returns the words spoken in [`sample_clip`], as punctuated by Whisper's `base.en` model.
+///
+/// Models differ in punctuation and casing, so compare through [`normalize_transcript`].
+pub fn sample_clip_transcript() -> &'static str {
+    SAMPLE_CLIP_TRANSCRIPT
+}
+
+/// Lowercases `text`, drops punctuation and collapses whitespace, so transcripts from
+/// different models can be compared word for word.
+pub fn normalize_transcript(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.chars().filter(|c| c.is_alphanumeric() || *c == '\'').collect::<String>().to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Fundamental frequency of the synthesized voice.
+const PITCH_HZ: f32 = 120.0;
+/// Formant centres and bandwidths of an /a/ vowel, used to shape the harmonics.
+const FORMANTS: [(f32, f32); 3] = [(700.0, 110.0), (1220.0, 120.0), (2600.0, 160.0)];
+/// Layout of the clip as (duration in ms, voiced) pairs.
+const LAYOUT_MS: [(u32, bool); 7] = [
+    (500, false),
+    (300, true),
+    (200, false),
+    (450, true),
+    (200, false),
+    (350, true),
+    (500, false),
+];
+
+/// Returns a deterministic 2.5s, 16kHz mono clip of speech-like audio without words.
+///
+/// Voiced regions are listed by [`synthetic_clip_speech_ranges`]; everything else is exact
+/// silence.
+pub fn synthetic_clip() -> Vec<f32> {
+    let mut clip = Vec::new();
+    for (duration_ms, voiced) in LAYOUT_MS {
+        let len = ms_to_samples(duration_ms);
+        if voiced {
+            clip.extend((0..len).map(|i| voiced_sample(i, len)));
+        } else {
+            clip.extend(std::iter::repeat_n(0.0f32, len));
+        }
+    }
+    clip
+}
+
+/// Sample ranges of [`synthetic_clip`] that contain voiced audio, in order.
+pub fn synthetic_clip_speech_ranges() -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    for (duration_ms, voiced) in LAYOUT_MS {
+        let len = ms_to_samples(duration_ms);
+        if voiced {
+            ranges.push(offset..offset + len);
+        }
+        offset += len;
+    }
+    ranges
+}
+
+fn ms_to_samples(ms: u32) -> usize {
+    (SAMPLE_CLIP_SAMPLE_RATE as usize * ms as usize) / 1000
+}
+
+/// One sample of a formant-shaped harmonic series with a raised-cosine envelope.
+fn voiced_sample(i: usize, len: usize) -> f32 {
+    let t = i as f32 / SAMPLE_CLIP_SAMPLE_RATE as f32;
+    let envelope = 0.5 - 0.5 * (2.0 * PI * i as f32 / len as f32).cos();
+    let nyquist = SAMPLE_CLIP_SAMPLE_RATE as f32 / 2.0;
+
+    let mut value = 0.0;
+    let mut harmonic = 1;
+    while PITCH_HZ * harmonic as f32 <= nyquist {
+        let freq = PITCH_HZ * harmonic as f32;
+        let weight: f32 = FORMANTS.iter()
+            .map(|&(centre, bandwidth)| 1.0 / (1.0 + ((freq - centre) / bandwidth).powi(2)))
+            .sum();
+        value += weight * (2.0 * PI * freq * t).sin() / harmonic as f32;
+        harmonic += 1;
+    }
+    (0.3 * envelope * value).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_synthetic_clip_is_deterministic_and_matches_ranges() {
+        let clip = synthetic_clip();
+        assert_eq!(clip, synthetic_clip());
+        assert_eq!(clip.len(), ms_to_samples(2500));
+
+        let ranges = synthetic_clip_speech_ranges();
+        assert_eq!(ranges.len(), 3);
+        for range in &ranges {
+            assert!(clip[range.clone()].iter().any(|s| s.abs() > 0.05));
+        }
+        assert!(clip[..ranges[0].start].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_sample_clip_is_speech_with_transcript() {
+        let clip = sample_clip();
+        assert_eq!(clip.len(), 11 * SAMPLE_CLIP_SAMPLE_RATE as usize);
+        assert!(clip.iter().any(|s| s.abs() > 0.1));
+        assert_eq!(
+            normalize_transcript(sample_clip_transcript()),
+            "and so my fellow americans ask not what your country can do for you ask what you can do for your country"
+        );
+    }
+}
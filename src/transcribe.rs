@@ -0,0 +1,795 @@
+//! One-shot transcription of complete audio buffers.
+//!
+//! Unlike [`crate::WhisperStream`], which captures from a microphone and emits events,
+//! these functions take audio that is already available (in memory or at a URL) and
+//! return a timestamped [`TranscriptionResult`].
+
+use std::io::{BufRead, BufReader, Read};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
+use log::{debug, info, warn};
+use crate::audio_utils::{decode_wav_samples, downmix_to_mono, pad_audio_if_needed, resample_to_16k, wav_read_error, MIN_WHISPER_SAMPLES, WHISPER_SAMPLE_RATE};
+use crate::cancel::CancellationToken;
+use crate::error::WhisperStreamError;
+#[cfg(feature = "compressed-audio")]
+use crate::audio_decode::decode_audio_bytes;
+use crate::model::{download_client, is_offline, DownloadOptions, Model};
+use crate::transcript::{merge_tokens_into_words, Segment, TokenTiming, TranscriptionResult, WordTiming};
+
+/// What Whisper does with the speech it hears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Task {
+    /// Write down the speech in the language it is spoken in.
+    #[default]
+    Transcribe,
+    /// Translate the speech to English text. Requires a multilingual model.
+    Translate,
+}
+
+impl Task {
+    /// Checks that `model` can perform this task.
+    ///
+    /// English-only (`.en`) models can't translate, so [`Task::Translate`] fails with
+    /// [`WhisperStreamError::Transcription`] for them.
+    pub fn check_model(self, model: Model) -> Result<(), WhisperStreamError> {
+        check_task(self, model.is_multilingual(), &format!("model {}", model))
+    }
+}
+
+fn check_task(task: Task, multilingual: bool, model_description: &str) -> Result<(), WhisperStreamError> {
+    if task == Task::Translate && !multilingual {
+        return Err(WhisperStreamError::Transcription(format!(
+            "Cannot translate with {}: it is English-only; use a multilingual model such as {}", model_description, Model::Base
+        )));
+    }
+    Ok(())
+}
+
+/// Options for a one-shot transcription call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscribeOptions {
+    /// Language spoken in the audio as a Whisper language code (e.g. "de"). `None` or
+    /// `"auto"` lets Whisper detect it; unknown codes are rejected. English-only models
+    /// always transcribe English.
+    pub language: Option<String>,
+    /// Number of CPU threads whisper decodes with, at least 1. `None` uses the available
+    /// parallelism, capped at 4.
+    pub n_threads: Option<i32>,
+    /// Whether to transcribe the speech or translate it to English.
+    pub task: Task,
+    /// Text that primes the decoder, e.g. names or jargon likely to be spoken, which biases
+    /// recognition towards their spelling. Prompts longer than the model accepts keep only
+    /// their end, with a warning.
+    pub initial_prompt: Option<String>,
+    /// Don't condition decoding on text from earlier decodes. On by default, as in whisper.cpp;
+    /// turning it off lets later windows of long audio see earlier text, which helps
+    /// consistency but can make Whisper hallucinate continuations of short utterances.
+    pub no_context: bool,
+    /// Force the decoder to produce a single segment. Meant for short, command-style audio;
+    /// together with `no_context` every call is decoded in isolation with minimal latency.
+    /// Audio longer than 30 seconds is still split by Whisper's window.
+    pub single_segment: bool,
+    /// Sampling temperature of the first decoding attempt, in `[0.0, 1.0]`. `0.0` decodes
+    /// greedily, which is the most accurate on clean audio.
+    pub temperature: f32,
+    /// How much the temperature rises each time a decode fails whisper's quality checks (e.g.
+    /// a repetition loop), in `[0.0, 1.0]`. `None` uses whisper.cpp's 0.2; `Some(0.0)`
+    /// disables the fallback.
+    pub temperature_inc: Option<f32>,
+    /// Identifies the model file `ctx` was loaded from, e.g. from
+    /// [`model_identity`](crate::model_identity). The transcription cache (`serde` feature)
+    /// only stores and serves results for calls that set it, so a fine-tuned model and an
+    /// updated model never receive another model's transcripts.
+    pub model_id: Option<String>,
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            language: Some("en".to_string()),
+            n_threads: None,
+            task: Task::Transcribe,
+            initial_prompt: None,
+            no_context: true,
+            single_segment: false,
+            temperature: 0.0,
+            temperature_inc: None,
+            model_id: None,
+        }
+    }
+}
+
+impl TranscribeOptions {
+    /// Returns the exact decoding parameters a [`transcribe`] call with these options uses.
+    pub fn decoding_params(&self) -> DecodingParams {
+        let defaults = DecodingParams::default();
+        DecodingParams {
+            language: self.language.as_deref().map(|lang| lang.trim().to_lowercase()).filter(|lang| lang != "auto"),
+            n_threads: self.n_threads.unwrap_or(defaults.n_threads),
+            translate: self.task == Task::Translate,
+            // whisper-rs can't pass NUL bytes on to whisper.cpp.
+            initial_prompt: self.initial_prompt.as_ref().map(|prompt| prompt.replace('\0', "")),
+            no_context: self.no_context,
+            single_segment: self.single_segment,
+            temperature: self.temperature,
+            temperature_inc: self.temperature_inc.unwrap_or(defaults.temperature_inc),
+            ..defaults
+        }
+    }
+
+    /// Like [`TranscribeOptions::decoding_params`], but rejects a thread count below 1 and a
+    /// task the model loaded in `ctx` can't perform.
+    ///
+    /// An initial prompt too long for the model is cut down to its last tokens.
+    fn decoding_params_for(&self, ctx: &WhisperContext) -> Result<DecodingParams, WhisperStreamError> {
+        check_task(self.task, ctx.is_multilingual(), "the loaded model")?;
+        let mut params = self.checked_decoding_params()?;
+        if let Some(prompt) = params.initial_prompt.take() {
+            params.initial_prompt = Some(fit_prompt(ctx, prompt)?);
+        }
+        Ok(params)
+    }
+
+    /// Like [`TranscribeOptions::decoding_params`], but rejects a thread count below 1,
+    /// temperatures outside `[0.0, 1.0]` and unknown language codes.
+    fn checked_decoding_params(&self) -> Result<DecodingParams, WhisperStreamError> {
+        let params = self.decoding_params();
+        if params.n_threads < 1 {
+            return Err(WhisperStreamError::Transcription(format!("n_threads must be at least 1 (got {})", params.n_threads)));
+        }
+        for (name, value) in [("temperature", params.temperature), ("temperature_inc", params.temperature_inc)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(WhisperStreamError::Transcription(format!("{} must be between 0.0 and 1.0 (got {})", name, value)));
+            }
+        }
+        if let Some(lang) = &params.language
+            && !WHISPER_LANGUAGES.contains(&lang.as_str())
+        {
+            return Err(WhisperStreamError::Transcription(format!(
+                "Unknown language code '{}'; use a Whisper language code such as \"en\" or \"de\", or \"auto\" to detect it", lang
+            )));
+        }
+        debug!("Transcribing with {} threads.", params.n_threads);
+        Ok(params)
+    }
+}
+
+/// Language codes Whisper can transcribe, in whisper.cpp's order.
+const WHISPER_LANGUAGES: [&str; 100] = [
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it", "id", "hi", "fi", "vi",
+    "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur", "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk",
+    "te", "fa", "lv", "bn", "sr", "az", "sl", "kn", "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw",
+    "gl", "mr", "pa", "si", "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln", "ha", "ba", "jw", "su", "yue",
+];
+
+/// Truncates `prompt` to the last tokens that fit the prompt budget of the model in `ctx`.
+fn fit_prompt(ctx: &WhisperContext, prompt: String) -> Result<String, WhisperStreamError> {
+    // whisper.cpp gives the prompt at most half of the text context.
+    let max_tokens = (ctx.n_text_ctx() / 2).max(1) as usize;
+    // No text tokenizes into more tokens than it has bytes.
+    let tokens = ctx.tokenize(&prompt, prompt.len() + 1).map_err(WhisperStreamError::from)?;
+    if tokens.len() <= max_tokens {
+        return Ok(prompt);
+    }
+    let mut bytes = Vec::new();
+    for &token in &tokens[tokens.len() - max_tokens..] {
+        bytes.extend_from_slice(ctx.token_to_cstr(token).map_err(WhisperStreamError::from)?.to_bytes());
+    }
+    // The cut may split a multi-byte character.
+    let truncated = String::from_utf8_lossy(&bytes).trim_start_matches('\u{FFFD}').to_string();
+    warn!("Initial prompt is {} tokens long but the model accepts {}; using only its end: {:?}", tokens.len(), max_tokens, truncated);
+    Ok(truncated)
+}
+
+/// A complete, reproducible snapshot of the whisper-rs decoding parameters used for a run.
+///
+/// Every field is applied explicitly when building the whisper-rs `FullParams`, so two runs
+/// with equal `DecodingParams` decode identically. Attach it to bug reports (see `to_json`
+/// with the `serde` feature) or diff two snapshots to see why runs differed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodingParams {
+    /// Beam width for beam search, or `None` for greedy decoding.
+    pub beam_size: Option<i32>,
+    /// Number of candidates sampled when decoding greedily.
+    pub best_of: i32,
+    /// Number of CPU threads whisper decodes with.
+    pub n_threads: i32,
+    /// Maximum tokens per segment (`0` means no limit).
+    pub max_tokens: i32,
+    /// Sampling temperature of the first decoding attempt; `0.0` decodes greedily.
+    pub temperature: f32,
+    /// How much the temperature rises after each decode that fails whisper's quality checks.
+    pub temperature_inc: f32,
+    /// Whether to translate the audio to English instead of transcribing it.
+    pub translate: bool,
+    /// Whisper language code of the audio, or `None` to auto-detect it.
+    pub language: Option<String>,
+    /// Text the decoder is primed with, e.g. names or jargon likely to be spoken.
+    pub initial_prompt: Option<String>,
+    /// Whether to ignore text from earlier decodes with the same state.
+    pub no_context: bool,
+    /// Whether to force all output into a single segment.
+    pub single_segment: bool,
+}
+
+impl Default for DecodingParams {
+    /// Mirrors whisper.cpp's own defaults for greedy decoding.
+    fn default() -> Self {
+        Self {
+            beam_size: None,
+            best_of: 1,
+            n_threads: std::thread::available_parallelism().map(|n| n.get().min(4) as i32).unwrap_or(4),
+            max_tokens: 0,
+            temperature: 0.0,
+            temperature_inc: 0.2,
+            translate: false,
+            language: None,
+            initial_prompt: None,
+            no_context: true,
+            single_segment: false,
+        }
+    }
+}
+
+impl DecodingParams {
+    /// Builds the whisper-rs parameters, applying every field of this snapshot.
+    pub(crate) fn full_params(&self) -> FullParams<'_, '_> {
+        let strategy = match self.beam_size {
+            Some(beam_size) => SamplingStrategy::BeamSearch { beam_size, patience: -1.0 },
+            None => SamplingStrategy::Greedy { best_of: self.best_of },
+        };
+        let mut params = FullParams::new(strategy);
+        params.set_n_threads(self.n_threads);
+        params.set_max_tokens(self.max_tokens);
+        params.set_temperature(self.temperature);
+        params.set_temperature_inc(self.temperature_inc);
+        params.set_translate(self.translate);
+        params.set_no_context(self.no_context);
+        params.set_single_segment(self.single_segment);
+        params.set_print_special(false);
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        // whisper.cpp falls back to English without a language; "auto" asks it to detect one.
+        params.set_language(Some(self.language.as_deref().unwrap_or("auto")));
+        if let Some(ref prompt) = self.initial_prompt {
+            params.set_initial_prompt(prompt);
+        }
+        params
+    }
+
+    /// Serializes the parameters to pretty-printed JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, WhisperStreamError> {
+        serde_json::to_string_pretty(self).map_err(|e| WhisperStreamError::Internal(format!("Failed to serialize decoding params: {}", e)))
+    }
+}
+
+/// Transcribes 16kHz mono `samples` with the given Whisper context.
+///
+/// Audio shorter than Whisper's minimum input length is padded with silence. With the
+/// `serde` feature, results are served from and saved to the transcription cache while it
+/// is enabled (see `set_transcription_cache_enabled`) and [`TranscribeOptions::model_id`]
+/// is set.
+pub fn transcribe(ctx: &WhisperContext, samples: &[f32], options: &TranscribeOptions) -> Result<TranscriptionResult, WhisperStreamError> {
+    transcribe_inner(ctx, samples, options, None)
+}
+
+/// Like [`transcribe`], but aborts as soon as `token` is cancelled.
+///
+/// Returns [`WhisperStreamError::Cancelled`] if the token was cancelled before or during
+/// inference; partial results are discarded.
+pub fn transcribe_cancellable(ctx: &WhisperContext, samples: &[f32], options: &TranscribeOptions, token: &CancellationToken) -> Result<TranscriptionResult, WhisperStreamError> {
+    transcribe_inner(ctx, samples, options, Some(token))
+}
+
+fn transcribe_inner(ctx: &WhisperContext, samples: &[f32], options: &TranscribeOptions, token: Option<&CancellationToken>) -> Result<TranscriptionResult, WhisperStreamError> {
+    if token.is_some_and(CancellationToken::is_cancelled) {
+        return Err(WhisperStreamError::Cancelled);
+    }
+    let decoding_params = options.decoding_params_for(ctx)?;
+    #[cfg(feature = "serde")]
+    let cache_key = if crate::transcription_cache::transcription_cache_enabled()
+        && let Some(model_id) = options.model_id.as_deref()
+    {
+        let key = crate::transcription_cache::cache_key(ctx, model_id, samples, &decoding_params)?;
+        if let Some(cached) = crate::transcription_cache::load(&key) {
+            info!("Using cached transcription result.");
+            return Ok(cached);
+        }
+        Some(key)
+    } else {
+        None
+    };
+
+    let mut state = ctx.create_state().map_err(WhisperStreamError::from)?;
+    let audio = pad_audio_if_needed(samples, MIN_WHISPER_SAMPLES);
+    let mut params = decoding_params.full_params();
+    if let Some(token) = token {
+        token.attach(&mut params);
+    }
+    let full_result = state.full(params, &audio);
+    if token.is_some_and(CancellationToken::is_cancelled) {
+        return Err(WhisperStreamError::Cancelled);
+    }
+    full_result.map_err(WhisperStreamError::from)?;
+
+    let num_segments = state.full_n_segments().map_err(WhisperStreamError::from)?;
+    let eot = ctx.token_eot();
+    let mut segments = Vec::with_capacity(num_segments.max(0) as usize);
+    for i in 0..num_segments {
+        // Whisper reports segment timestamps in units of 10ms.
+        let t0 = state.full_get_segment_t0(i).map_err(WhisperStreamError::from)?;
+        let t1 = state.full_get_segment_t1(i).map_err(WhisperStreamError::from)?;
+        let text = state.full_get_segment_text(i).map_err(WhisperStreamError::from)?;
+        // Average over text tokens only; all of whisper's special tokens come after end-of-text.
+        let (mut logprob_sum, mut text_tokens) = (0.0f32, 0usize);
+        for token in 0..state.full_n_tokens(i).map_err(WhisperStreamError::from)? {
+            let data = state.full_get_token_data(i, token).map_err(WhisperStreamError::from)?;
+            if data.id < eot {
+                logprob_sum += data.plog;
+                text_tokens += 1;
+            }
+        }
+        segments.push(Segment {
+            start: t0 as f64 / 100.0,
+            end: t1 as f64 / 100.0,
+            text,
+            avg_logprob: logprob_sum / text_tokens.max(1) as f32,
+            no_speech_prob: None,
+        });
+    }
+    let language = state.full_lang_id_from_state().ok()
+        .and_then(whisper_rs::get_lang_str)
+        .map(str::to_string);
+    let result = TranscriptionResult { segments, language };
+    #[cfg(feature = "serde")]
+    if let Some(key) = cache_key {
+        crate::transcription_cache::store(&key, &result);
+    }
+    Ok(result)
+}
+
+/// Transcribes 16kHz mono `samples` into individual words with their timings.
+///
+/// Enables whisper's token-level timestamps and merges each segment's word-piece tokens into
+/// whole words, dropping special tokens, e.g. for karaoke-style highlighting or subtitle
+/// styling. Token timestamps are estimates and can be off by a few tens of milliseconds.
+/// Results are not cached.
+pub fn transcribe_words(ctx: &WhisperContext, samples: &[f32], options: &TranscribeOptions) -> Result<Vec<WordTiming>, WhisperStreamError> {
+    let decoding_params = options.decoding_params_for(ctx)?;
+    let mut params = decoding_params.full_params();
+    params.set_token_timestamps(true);
+    let mut state = ctx.create_state().map_err(WhisperStreamError::from)?;
+    let audio = pad_audio_if_needed(samples, MIN_WHISPER_SAMPLES);
+    state.full(params, &audio).map_err(WhisperStreamError::from)?;
+
+    let end_time = samples.len() as f64 / WHISPER_SAMPLE_RATE as f64;
+    let eot = ctx.token_eot();
+    let mut words = Vec::new();
+    for segment in 0..state.full_n_segments().map_err(WhisperStreamError::from)? {
+        let n_tokens = state.full_n_tokens(segment).map_err(WhisperStreamError::from)?;
+        let mut tokens = Vec::with_capacity(n_tokens.max(0) as usize);
+        for token in 0..n_tokens {
+            let data = state.full_get_token_data(segment, token).map_err(WhisperStreamError::from)?;
+            tokens.push(TokenTiming {
+                text: state.full_get_token_text_lossy(segment, token).map_err(WhisperStreamError::from)?,
+                // Token timestamps are in units of 10ms; padding can push them past the real end.
+                start: (data.t0 as f64 / 100.0).min(end_time),
+                end: (data.t1 as f64 / 100.0).min(end_time),
+                // All of whisper's special tokens come after the end-of-text token.
+                special: data.id >= eot,
+            });
+        }
+        words.extend(merge_tokens_into_words(&tokens));
+    }
+    Ok(words)
+}
+
+/// Transcribes long 16kHz mono `samples` in fixed windows, returning one continuous timeline.
+///
+/// Windows are `window_secs` long and consecutive windows share `overlap_secs` of audio, so
+/// words cut at one window's edge are heard whole by the next. Each window's segment
+/// timestamps are shifted to the position of the window in `samples`. Where two windows
+/// overlap, the cut is made at the middle of the overlap: segments centred before it are
+/// taken from the earlier window and the rest from the later one, so nothing is duplicated.
+pub fn transcribe_long(ctx: &WhisperContext, samples: &[f32], window_secs: f32, overlap_secs: f32, options: &TranscribeOptions) -> Result<Vec<Segment>, WhisperStreamError> {
+    let mut merged: Vec<Segment> = Vec::new();
+    for window in plan_windows(samples.len(), window_secs, overlap_secs)? {
+        let window_segments = transcribe_window(ctx, samples, &window, options)?;
+        merge_window_segments(&mut merged, window_segments, seconds(window.keep_start));
+    }
+    Ok(merged)
+}
+
+/// Transcribes long 16kHz mono `samples` window by window, yielding each window's text as
+/// soon as it is decoded.
+///
+/// Windows are laid out as in [`transcribe_long`]: `chunk_secs` long, sharing `overlap_secs`
+/// of audio with their neighbours, with the final short window padded with silence by
+/// [`transcribe`]. Each item is `(start_time, end_time, text)` in seconds, where the times
+/// cover only the part of the window it owns: overlaps are split at their middle and a window
+/// keeps only the segments centred in its part, so words heard by two windows are reported
+/// once. The items tile `samples` without gaps.
+///
+/// Fails up front if the overlap is not shorter than the chunk. After a window fails to
+/// transcribe, its error is yielded and the iterator ends.
+pub fn transcribe_chunked<'a>(ctx: &'a WhisperContext, samples: &'a [f32], chunk_secs: f32, overlap_secs: f32, options: &'a TranscribeOptions) -> Result<TranscribeChunks<'a>, WhisperStreamError> {
+    Ok(TranscribeChunks {
+        ctx,
+        samples,
+        options,
+        windows: plan_windows(samples.len(), chunk_secs, overlap_secs)?.into_iter(),
+    })
+}
+
+/// Iterator over the windows of a long transcription, returned by [`transcribe_chunked`].
+pub struct TranscribeChunks<'a> {
+    ctx: &'a WhisperContext,
+    samples: &'a [f32],
+    options: &'a TranscribeOptions,
+    windows: std::vec::IntoIter<Window>,
+}
+
+impl Iterator for TranscribeChunks<'_> {
+    type Item = Result<(f64, f64, String), WhisperStreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let window = self.windows.next()?;
+        match transcribe_window(self.ctx, self.samples, &window, self.options) {
+            Ok(segments) => Some(Ok((seconds(window.keep_start), seconds(window.keep_end), window_text(&segments, &window)))),
+            Err(e) => {
+                self.windows = Vec::new().into_iter();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// One window of a long transcription, in samples.
+///
+/// `start..end` is the audio that is decoded; `keep_start..keep_end` is the part of it whose
+/// segments belong to this window once overlaps are split at their middle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Window {
+    start: usize,
+    end: usize,
+    keep_start: usize,
+    keep_end: usize,
+}
+
+/// Lays out overlapping windows over `len` samples, always returning at least one window.
+fn plan_windows(len: usize, window_secs: f32, overlap_secs: f32) -> Result<Vec<Window>, WhisperStreamError> {
+    let window_len = (window_secs * WHISPER_SAMPLE_RATE as f32) as usize;
+    let overlap_len = (overlap_secs.max(0.0) * WHISPER_SAMPLE_RATE as f32) as usize;
+    if window_len == 0 || overlap_len >= window_len {
+        return Err(WhisperStreamError::Transcription(format!(
+            "Invalid window: {}s window with {}s overlap; the window must be longer than the overlap", window_secs, overlap_secs
+        )));
+    }
+    let hop = window_len - overlap_len;
+
+    let mut windows: Vec<Window> = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + window_len).min(len);
+        let keep_start = if start == 0 { 0 } else { start + overlap_len / 2 };
+        if let Some(previous) = windows.last_mut() {
+            previous.keep_end = keep_start;
+        }
+        windows.push(Window { start, end, keep_start, keep_end: end });
+        if end >= len {
+            return Ok(windows);
+        }
+        start += hop;
+    }
+}
+
+/// Transcribes one window and shifts its segment timestamps to the window's place in `samples`.
+fn transcribe_window(ctx: &WhisperContext, samples: &[f32], window: &Window, options: &TranscribeOptions) -> Result<Vec<Segment>, WhisperStreamError> {
+    let offset = seconds(window.start);
+    let end_time = seconds(window.end);
+    let mut segments = transcribe(ctx, &samples[window.start..window.end], options)?.segments;
+    for segment in &mut segments {
+        // Padding can push timestamps past the real end of a short final window.
+        segment.start = (segment.start + offset).min(end_time);
+        segment.end = (segment.end + offset).min(end_time);
+    }
+    Ok(segments)
+}
+
+/// Joins the text of the segments centred in the part of the timeline `window` keeps.
+fn window_text(segments: &[Segment], window: &Window) -> String {
+    let (keep_start, keep_end) = (seconds(window.keep_start), seconds(window.keep_end));
+    let last = window.keep_end == window.end;
+    segments.iter()
+        .filter(|s| {
+            let midpoint = (s.start + s.end) / 2.0;
+            midpoint >= keep_start && (midpoint < keep_end || last)
+        })
+        .map(|s| s.text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn seconds(samples: usize) -> f64 {
+    samples as f64 / WHISPER_SAMPLE_RATE as f64
+}
+
+/// Appends a window's already offset segments to `merged`, cutting both at `boundary` (in seconds).
+fn merge_window_segments(merged: &mut Vec<Segment>, window_segments: Vec<Segment>, boundary: f64) {
+    let midpoint = |s: &Segment| (s.start + s.end) / 2.0;
+    merged.retain(|s| midpoint(s) < boundary);
+    let previous_end = merged.last().map_or(0.0, |s| s.end);
+    for mut segment in window_segments.into_iter().filter(|s| midpoint(s) >= boundary) {
+        // Keep the timeline monotonic across the cut.
+        segment.start = segment.start.max(previous_end);
+        segment.end = segment.end.max(segment.start);
+        merged.push(segment);
+    }
+}
+
+/// Fetches a remote audio file and transcribes it, using the default [`DownloadOptions`].
+///
+/// See [`transcribe_url_with_options`].
+pub fn transcribe_url(ctx: &WhisperContext, url: &str, options: &TranscribeOptions) -> Result<TranscriptionResult, WhisperStreamError> {
+    transcribe_url_with_options(ctx, url, options, &DownloadOptions::default())
+}
+
+/// Fetches a remote audio file with the timeouts and proxy of `download` and transcribes it.
+///
+/// WAV bodies are decoded as they are received, so only the decoded samples are held in
+/// memory, never the raw download; any sample rate is resampled to 16kHz and multi-channel
+/// audio is averaged down to mono. With the `compressed-audio` feature, MP3, FLAC and Ogg
+/// Vorbis bodies are buffered and decoded too; without it they fail with
+/// [`WhisperStreamError::UnsupportedAudioFormat`]. Nothing is fetched in offline mode (see
+/// [`set_offline`](crate::set_offline)).
+pub fn transcribe_url_with_options(ctx: &WhisperContext, url: &str, options: &TranscribeOptions, download: &DownloadOptions) -> Result<TranscriptionResult, WhisperStreamError> {
+    let samples = fetch_audio(url, download)?;
+    transcribe(ctx, &samples, options)
+}
+
+/// Downloads `url` and decodes it into 16kHz mono samples.
+fn fetch_audio(url: &str, download: &DownloadOptions) -> Result<Vec<f32>, WhisperStreamError> {
+    if download.offline || is_offline() {
+        return Err(WhisperStreamError::Transcription(format!("Cannot fetch audio from {}: downloads are disabled (offline mode)", url)));
+    }
+    let resp = download_client(download)?.get(url).send()
+        .map_err(|e| WhisperStreamError::Transcription(format!("Failed to fetch audio from {}: {}", url, e)))?;
+    if !resp.status().is_success() {
+        return Err(WhisperStreamError::Transcription(format!("Failed to fetch audio from {}: HTTP Status {}", url, resp.status())));
+    }
+
+    info!("Decoding remote audio from {}...", url);
+    let mut body = BufReader::new(resp);
+    let magic = body.fill_buf().map_err(WhisperStreamError::from)?;
+    if magic.starts_with(b"RIFF") {
+        return read_wav_mono_16k(body);
+    }
+    decode_compressed_body(body, url)
+}
+
+#[cfg(feature = "compressed-audio")]
+fn decode_compressed_body<R: Read>(mut body: R, url: &str) -> Result<Vec<f32>, WhisperStreamError> {
+    let mut bytes = Vec::new();
+    body.read_to_end(&mut bytes).map_err(WhisperStreamError::from)?;
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path.rsplit_once('/').map_or(path, |(_, name)| name).rsplit_once('.').map(|(_, ext)| ext);
+    decode_audio_bytes(bytes, extension, url).map(|(samples, _)| samples)
+}
+
+#[cfg(not(feature = "compressed-audio"))]
+fn decode_compressed_body<R: Read>(_body: R, url: &str) -> Result<Vec<f32>, WhisperStreamError> {
+    Err(WhisperStreamError::UnsupportedAudioFormat {
+        details: format!("{} is not a WAV file; enable the compressed-audio feature to decode MP3, FLAC or Ogg Vorbis", url),
+    })
+}
+
+/// Decodes a WAV stream into 16kHz mono f32 samples in `[-1.0, 1.0]`.
+fn read_wav_mono_16k<R: Read>(reader: R) -> Result<Vec<f32>, WhisperStreamError> {
+    let wav = hound::WavReader::new(reader).map_err(wav_read_error)?;
+    let spec = wav.spec();
+    let (interleaved, _) = decode_wav_samples(wav)?;
+    let mono = if spec.channels == 1 { interleaved } else { downmix_to_mono(&interleaved, spec.channels) };
+    Ok(resample_to_16k(&mono, spec.sample_rate)?.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn wav_bytes(spec: hound::WavSpec, samples: &[i16]) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for &s in samples {
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+        cursor.into_inner()
+    }
+
+    #[test]
+    fn test_read_wav_mono_16k_downmixes_stereo() {
+        let spec = hound::WavSpec { channels: 2, sample_rate: 16000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let bytes = wav_bytes(spec, &[16384, 0, -16384, -16384]);
+        let samples = read_wav_mono_16k(Cursor::new(bytes)).unwrap();
+        assert_eq!(samples, vec![0.25, -0.5]);
+    }
+
+    #[test]
+    fn test_read_wav_mono_16k_resamples_other_rates() {
+        let spec = hound::WavSpec { channels: 1, sample_rate: 44100, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let bytes = wav_bytes(spec, &[0; 44100]);
+        assert_eq!(read_wav_mono_16k(Cursor::new(bytes)).unwrap().len(), 16000);
+    }
+
+    #[test]
+    fn test_fetch_audio_honours_offline_mode() {
+        let options = DownloadOptions { offline: true, ..DownloadOptions::default() };
+        // Nothing listens on port 9; the offline check must fail before connecting.
+        match fetch_audio("http://127.0.0.1:9/clip.wav", &options) {
+            Err(WhisperStreamError::Transcription(message)) => assert!(message.contains("offline"), "{}", message),
+            other => panic!("expected an offline error, got {:?}", other.map(|s| s.len())),
+        }
+    }
+
+    #[cfg(feature = "compressed-audio")]
+    #[test]
+    fn test_fetch_audio_decodes_mp3() {
+        use std::io::Write;
+        let body = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/silence-44k.mp3")).unwrap();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/clip.mp3", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+            let _ = stream.write_all(&body);
+        });
+
+        let samples = fetch_audio(&url, &DownloadOptions::default()).unwrap();
+        assert!(!samples.is_empty());
+        assert!(samples.iter().all(|s| s.abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_merge_window_segments_cuts_overlap_at_boundary() {
+        let segment = |start: f64, end: f64, text: &str| Segment { start, end, text: text.to_string(), ..Segment::default() };
+        let mut merged = Vec::new();
+        merge_window_segments(&mut merged, vec![segment(0.0, 4.0, "a"), segment(4.0, 9.5, "b"), segment(9.5, 10.0, "c")], 0.0);
+        // Second window covers 8..18s; the overlap 8..10s is cut at 9s.
+        merge_window_segments(&mut merged, vec![segment(8.0, 9.4, "b-tail"), segment(9.2, 12.0, "c"), segment(12.0, 18.0, "d")], 9.0);
+
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "b", "c", "d"]);
+        assert_eq!(merged[2].start, 9.5);
+        assert!(merged.windows(2).all(|w| w[0].end <= w[1].start));
+    }
+
+    #[test]
+    fn test_plan_windows_splits_overlaps_at_their_middle() {
+        // 25s of audio in 10s windows with 2s overlap: windows start every 8s.
+        let windows = plan_windows(25 * 16000, 10.0, 2.0).unwrap();
+        let bounds: Vec<(f64, f64, f64, f64)> = windows.iter()
+            .map(|w| (seconds(w.start), seconds(w.end), seconds(w.keep_start), seconds(w.keep_end)))
+            .collect();
+        assert_eq!(bounds, vec![
+            (0.0, 10.0, 0.0, 9.0),
+            (8.0, 18.0, 9.0, 17.0),
+            (16.0, 25.0, 17.0, 25.0),
+        ]);
+    }
+
+    #[test]
+    fn test_plan_windows_handles_short_and_invalid_input() {
+        assert_eq!(plan_windows(0, 10.0, 2.0).unwrap(), vec![Window { start: 0, end: 0, keep_start: 0, keep_end: 0 }]);
+        assert_eq!(plan_windows(16000, 10.0, 2.0).unwrap().len(), 1);
+        assert!(matches!(plan_windows(16000, 2.0, 2.0), Err(WhisperStreamError::Transcription(_))));
+        assert!(matches!(plan_windows(16000, 0.0, 0.0), Err(WhisperStreamError::Transcription(_))));
+    }
+
+    #[test]
+    fn test_window_text_drops_words_owned_by_neighbours() {
+        let segment = |start: f64, end: f64, text: &str| Segment { start, end, text: text.to_string(), ..Segment::default() };
+        let window = Window { start: 8 * 16000, end: 18 * 16000, keep_start: 9 * 16000, keep_end: 17 * 16000 };
+        let segments = vec![
+            segment(8.0, 9.4, " tail of previous"),
+            segment(9.2, 12.0, " hello"),
+            segment(12.0, 16.0, " world"),
+            segment(16.5, 18.0, " head of next"),
+        ];
+        assert_eq!(window_text(&segments, &window), "hello world");
+    }
+
+    #[test]
+    fn test_decoding_params_reflect_options() {
+        let options = TranscribeOptions { language: Some("de".to_string()), ..TranscribeOptions::default() };
+        let params = options.decoding_params();
+        assert_eq!(params.language.as_deref(), Some("de"));
+        assert_eq!(params.beam_size, None);
+        assert!(params.n_threads >= 1);
+    }
+
+    #[test]
+    fn test_translate_requires_multilingual_model() {
+        match Task::Translate.check_model(Model::BaseEn) {
+            Err(WhisperStreamError::Transcription(message)) => assert!(message.contains("English-only"), "{}", message),
+            other => panic!("expected a Transcription error, got {:?}", other),
+        }
+        assert!(Task::Translate.check_model(Model::Base).is_ok());
+        assert!(Task::Transcribe.check_model(Model::BaseEn).is_ok());
+
+        let options = TranscribeOptions { task: Task::Translate, ..TranscribeOptions::default() };
+        assert!(options.decoding_params().translate);
+    }
+
+    #[test]
+    fn test_initial_prompt_is_forwarded() {
+        let options = TranscribeOptions { initial_prompt: Some("WhisperStream, CoreML\0".to_string()), ..TranscribeOptions::default() };
+        assert_eq!(options.decoding_params().initial_prompt.as_deref(), Some("WhisperStream, CoreML"));
+        assert_eq!(TranscribeOptions::default().decoding_params().initial_prompt, None);
+    }
+
+    #[test]
+    fn test_language_codes_are_validated() {
+        let options = |lang: Option<&str>| TranscribeOptions { language: lang.map(str::to_string), ..TranscribeOptions::default() };
+        assert_eq!(options(Some("DE")).checked_decoding_params().unwrap().language.as_deref(), Some("de"));
+        assert_eq!(options(Some("auto")).checked_decoding_params().unwrap().language, None);
+        assert_eq!(options(None).checked_decoding_params().unwrap().language, None);
+        match options(Some("xx")).checked_decoding_params() {
+            Err(WhisperStreamError::Transcription(message)) => assert!(message.contains("'xx'"), "{}", message),
+            other => panic!("expected a Transcription error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_no_context_and_single_segment_are_forwarded() {
+        let defaults = TranscribeOptions::default().decoding_params();
+        assert!(defaults.no_context);
+        assert!(!defaults.single_segment);
+
+        let options = TranscribeOptions { no_context: false, single_segment: true, ..TranscribeOptions::default() };
+        let params = options.decoding_params();
+        assert!(!params.no_context);
+        assert!(params.single_segment);
+    }
+
+    #[test]
+    fn test_temperature_range_is_validated() {
+        let options = |temperature: f32, temperature_inc: Option<f32>| TranscribeOptions { temperature, temperature_inc, ..TranscribeOptions::default() };
+        let defaults = options(0.0, None).checked_decoding_params().unwrap();
+        assert_eq!((defaults.temperature, defaults.temperature_inc), (0.0, 0.2));
+        let params = options(1.0, Some(0.0)).checked_decoding_params().unwrap();
+        assert_eq!((params.temperature, params.temperature_inc), (1.0, 0.0));
+        assert!(options(1.0, Some(1.0)).checked_decoding_params().is_ok());
+
+        for (temperature, temperature_inc) in [(-0.01, None), (1.01, None), (f32::NAN, None), (0.0, Some(-0.1)), (0.0, Some(1.5))] {
+            assert!(
+                matches!(options(temperature, temperature_inc).checked_decoding_params(), Err(WhisperStreamError::Transcription(_))),
+                "accepted temperature {} with increment {:?}", temperature, temperature_inc
+            );
+        }
+    }
+
+    #[test]
+    fn test_n_threads_is_applied_and_validated() {
+        let options = TranscribeOptions { n_threads: Some(8), ..TranscribeOptions::default() };
+        assert_eq!(options.checked_decoding_params().unwrap().n_threads, 8);
+
+        let options = TranscribeOptions { n_threads: Some(0), ..TranscribeOptions::default() };
+        assert!(matches!(options.checked_decoding_params(), Err(WhisperStreamError::Transcription(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_decoding_params_to_json() {
+        let json = TranscribeOptions::default().decoding_params().to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["language"], "en");
+        assert_eq!(value["translate"], false);
+    }
+}
@@ -0,0 +1,486 @@
+//! Timestamped transcription results and helpers that analyze and format them.
+
+use std::io::Write;
+use crate::error::WhisperStreamError;
+
+/// A transcribed span of audio with its position on the timeline.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    /// Start time in seconds.
+    pub start: f64,
+    /// End time in seconds.
+    pub end: f64,
+    /// The transcribed text.
+    pub text: String,
+    /// Mean log probability of the segment's text tokens; closer to 0.0 is more certain.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub avg_logprob: f32,
+    /// Probability that the segment's audio contains no speech at all, when known. whisper-rs
+    /// does not expose whisper.cpp's per-segment value yet, so transcription leaves this `None`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub no_speech_prob: Option<f32>,
+}
+
+/// Below this average log probability a segment's text is treated as a guess; it is the
+/// threshold whisper itself uses to fall back to a higher temperature.
+const MIN_CONFIDENT_LOGPROB: f32 = -1.0;
+
+impl Segment {
+    /// Whether the segment is likely real speech rather than text hallucinated over silence.
+    ///
+    /// A segment is confident when its `no_speech_prob` is at most `no_speech_threshold` and
+    /// its `avg_logprob` is at least -1.0. Whisper's usual no-speech threshold is 0.6. Without a
+    /// `no_speech_prob` only `avg_logprob` is checked.
+    pub fn is_confident(&self, no_speech_threshold: f32) -> bool {
+        self.no_speech_prob.is_none_or(|p| p <= no_speech_threshold) && self.avg_logprob >= MIN_CONFIDENT_LOGPROB
+    }
+}
+
+/// A single word with the time span it was spoken in.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WordTiming {
+    /// The word, including any punctuation attached to it.
+    pub word: String,
+    /// Start time in seconds.
+    pub start: f64,
+    /// End time in seconds.
+    pub end: f64,
+}
+
+/// A decoded token with its timestamps, as reported by whisper.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TokenTiming {
+    pub(crate) text: String,
+    pub(crate) start: f64,
+    pub(crate) end: f64,
+    /// Control tokens such as `[_BEG_]` or timestamp markers, which carry no text.
+    pub(crate) special: bool,
+}
+
+/// Merges a segment's tokens into words.
+///
+/// Whisper's tokens are word pieces: a token starting with whitespace begins a new word, and
+/// any other token (a subword or punctuation) continues the current one. Special tokens are
+/// dropped. A word spans from the start of its first token to the end of its last.
+pub(crate) fn merge_tokens_into_words(tokens: &[TokenTiming]) -> Vec<WordTiming> {
+    let mut words: Vec<WordTiming> = Vec::new();
+    let mut continues_word = false;
+    for token in tokens.iter().filter(|t| !t.special) {
+        let starts_word = token.text.starts_with(char::is_whitespace);
+        let text = token.text.trim();
+        if text.is_empty() {
+            continues_word = false;
+            continue;
+        }
+        match words.last_mut() {
+            Some(word) if continues_word && !starts_word => {
+                word.word.push_str(text);
+                word.end = token.end.max(word.end);
+            }
+            _ => words.push(WordTiming { word: text.to_string(), start: token.start, end: token.end.max(token.start) }),
+        }
+        continues_word = !token.text.ends_with(char::is_whitespace);
+    }
+    words
+}
+
+/// The timestamped output of transcribing a buffer of audio.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TranscriptionResult {
+    pub segments: Vec<Segment>,
+    /// Code of the language the audio was transcribed as, detected by Whisper unless one was
+    /// set (e.g. "de"). `None` if it is unknown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub language: Option<String>,
+}
+
+impl TranscriptionResult {
+    /// Returns the text of all segments joined together.
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+/// Estimates the speaking rate of a result in words per minute.
+///
+/// Words are counted across all segments and divided by the span from the earliest
+/// segment start to the latest segment end. Returns `0.0` if there are no words or the
+/// span has zero (or negative) duration.
+pub fn words_per_minute(result: &TranscriptionResult) -> f32 {
+    let word_count: usize = result.segments.iter()
+        .map(|s| s.text.split_whitespace().count())
+        .sum();
+    if word_count == 0 {
+        return 0.0;
+    }
+
+    let start = result.segments.iter().map(|s| s.start).fold(f64::INFINITY, f64::min);
+    let end = result.segments.iter().map(|s| s.end).fold(f64::NEG_INFINITY, f64::max);
+    let duration_secs = end - start;
+    if duration_secs <= 0.0 {
+        return 0.0;
+    }
+
+    (word_count as f64 / (duration_secs / 60.0)) as f32
+}
+
+/// Words that end in a period without ending a sentence, compared case-insensitively.
+const ABBREVIATIONS: [&str; 16] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd", "no", "approx",
+];
+
+/// Re-chunks `segments` into one segment per sentence.
+///
+/// Sentences end at `.`, `!` or `?` (plus any closing quotes or brackets) followed by
+/// whitespace or the end of the text, and may span several input segments. A period does
+/// not end a sentence after a common abbreviation ("Dr.", "e.g."), a single-letter initial,
+/// or when the next word starts in lowercase. Timestamps are interpolated linearly by
+/// character position within each input segment, so they are estimates. Text after the last
+/// sentence-ending punctuation becomes a final sentence. Output text is trimmed. A sentence
+/// takes the lowest confidence of the segments it draws text from.
+pub fn split_into_sentences(segments: &[Segment]) -> Vec<Segment> {
+    // Every character with its interpolated (start, end) time, and the segment it came from.
+    let mut chars: Vec<(char, f64, f64)> = Vec::new();
+    let mut sources: Vec<usize> = Vec::new();
+    for (index, segment) in segments.iter().enumerate() {
+        let count = segment.text.chars().count();
+        let step = (segment.end - segment.start) / count.max(1) as f64;
+        chars.extend(segment.text.chars().enumerate().map(|(i, c)| {
+            (c, segment.start + step * i as f64, segment.start + step * (i + 1) as f64)
+        }));
+        sources.resize(chars.len(), index);
+    }
+
+    let mut sentences = Vec::new();
+    let mut sentence_start = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        if !matches!(chars[i].0, '.' | '!' | '?') {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < chars.len() && matches!(chars[end].0, '.' | '!' | '?' | '"' | '\'' | ')' | ']') {
+            end += 1;
+        }
+        let at_boundary = end == chars.len() || chars[end].0.is_whitespace();
+        if at_boundary && !(chars[i].0 == '.' && is_non_terminal_period(&chars[sentence_start..i], &chars[end..])) {
+            push_sentence(&mut sentences, &chars[sentence_start..end], &sources[sentence_start..end], segments);
+            sentence_start = end;
+        }
+        i = end;
+    }
+    push_sentence(&mut sentences, &chars[sentence_start..], &sources[sentence_start..], segments);
+    sentences
+}
+
+/// Whether a period between `before` and `after` is part of an abbreviation rather than a sentence end.
+fn is_non_terminal_period(before: &[(char, f64, f64)], after: &[(char, f64, f64)]) -> bool {
+    let word_len = before.iter().rev().take_while(|(c, _, _)| !c.is_whitespace()).count();
+    let word: String = before[before.len() - word_len..].iter()
+        .map(|(c, _, _)| c.to_ascii_lowercase())
+        .collect();
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let is_initial = word.chars().count() == 1 && word.chars().all(char::is_alphabetic);
+    let next_is_lowercase = after.iter()
+        .map(|(c, _, _)| *c)
+        .find(|c| !c.is_whitespace())
+        .is_some_and(char::is_lowercase);
+    is_initial || next_is_lowercase || ABBREVIATIONS.contains(&word)
+}
+
+fn push_sentence(sentences: &mut Vec<Segment>, chars: &[(char, f64, f64)], sources: &[usize], segments: &[Segment]) {
+    let first = chars.iter().position(|(c, _, _)| !c.is_whitespace());
+    let last = chars.iter().rposition(|(c, _, _)| !c.is_whitespace());
+    if let (Some(first), Some(last)) = (first, last) {
+        let sources = || sources[first..=last].iter().map(|&i| &segments[i]);
+        sentences.push(Segment {
+            start: chars[first].1,
+            end: chars[last].2,
+            text: chars[first..=last].iter().map(|(c, _, _)| c).collect(),
+            avg_logprob: sources().map(|s| s.avg_logprob).fold(0.0, f32::min),
+            no_speech_prob: sources().filter_map(|s| s.no_speech_prob).reduce(f32::max),
+        });
+    }
+}
+
+/// How timestamps are rendered by [`write_timestamped_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+    /// `[HH:MM:SS]`; hours keep counting past 24.
+    #[default]
+    Clock,
+    /// `[123.45]`, seconds with two decimals.
+    Seconds,
+}
+
+/// Writes one `[timestamp] text` line per segment, using each segment's start time.
+///
+/// Segments whose text is empty after trimming are skipped.
+pub fn write_timestamped_text<W: Write>(segments: &[Segment], writer: &mut W, format: TimestampFormat) -> Result<(), WhisperStreamError> {
+    for segment in segments {
+        let text = segment.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let start = segment.start.max(0.0);
+        match format {
+            TimestampFormat::Clock => {
+                let total_secs = start as u64;
+                writeln!(writer, "[{:02}:{:02}:{:02}] {}", total_secs / 3600, (total_secs / 60) % 60, total_secs % 60, text)?;
+            }
+            TimestampFormat::Seconds => writeln!(writer, "[{:.2}] {}", start, text)?,
+        }
+    }
+    Ok(())
+}
+
+/// Formats `segments` as an SRT subtitle file.
+///
+/// Cues are numbered from 1 and separated by blank lines, with `HH:MM:SS,mmm` timestamps
+/// rounded to the millisecond. Segments whose text is empty after trimming are skipped
+/// without leaving a gap in the numbering, and an end time before the start is clamped to it.
+pub fn to_srt(segments: &[Segment]) -> String {
+    let mut srt = String::new();
+    let cues = segments.iter().filter(|s| !s.text.trim().is_empty());
+    for (index, segment) in cues.enumerate() {
+        let start = segment.start.max(0.0);
+        let end = segment.end.max(start);
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1, cue_timestamp(start, ','), cue_timestamp(end, ','), segment.text.trim()
+        ));
+    }
+    srt
+}
+
+/// Formats `segments` as a WebVTT file.
+///
+/// The output starts with the `WEBVTT` header and has one cue per segment with
+/// `HH:MM:SS.mmm` timestamps, separated by blank lines. Segments whose text is empty after
+/// trimming are skipped. Cue times never go backwards: a cue starting before the previous
+/// one ended is moved to start at that end, as WebVTT players expect ordered cues.
+pub fn to_vtt(segments: &[Segment]) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+    let mut previous_end = 0.0_f64;
+    for segment in segments.iter().filter(|s| !s.text.trim().is_empty()) {
+        let start = segment.start.max(previous_end);
+        let end = segment.end.max(start);
+        previous_end = end;
+        vtt.push_str(&format!(
+            "\n{} --> {}\n{}\n",
+            cue_timestamp(start, '.'), cue_timestamp(end, '.'), segment.text.trim()
+        ));
+    }
+    vtt
+}
+
+/// Serializes `segments` to pretty-printed JSON for other tools to consume.
+///
+/// The output is an object holding the `model` name, the detected `language` (`null` if
+/// unknown) and a `segments` array of `{ "start", "end", "text", "avg_logprob",
+/// "no_speech_prob" }` objects, with times in seconds. `no_speech_prob` is `null` when
+/// whisper didn't report it.
+#[cfg(feature = "serde")]
+pub fn to_json(segments: &[Segment], model: &str, language: Option<&str>) -> Result<String, WhisperStreamError> {
+    #[derive(serde::Serialize)]
+    struct JsonTranscript<'a> {
+        model: &'a str,
+        language: Option<&'a str>,
+        segments: &'a [Segment],
+    }
+
+    serde_json::to_string_pretty(&JsonTranscript { model, language, segments })
+        .map_err(|e| WhisperStreamError::Internal(format!("Failed to serialize transcript: {}", e)))
+}
+
+/// Formats `secs` as `HH:MM:SS` followed by `separator` and milliseconds.
+fn cue_timestamp(secs: f64, separator: char) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    let total_secs = millis / 1000;
+    format!("{:02}:{:02}:{:02}{}{:03}", total_secs / 3600, (total_secs / 60) % 60, total_secs % 60, separator, millis % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str) -> Segment {
+        Segment { start, end, text: text.to_string(), ..Segment::default() }
+    }
+
+    #[test]
+    fn test_words_per_minute() {
+        let result = TranscriptionResult {
+            segments: vec![
+                segment(0.0, 10.0, " one two three four five"),
+                segment(10.0, 30.0, " six seven eight nine ten"),
+            ],
+            ..TranscriptionResult::default()
+        };
+        assert!((words_per_minute(&result) - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_words_per_minute_empty_or_zero_duration() {
+        assert_eq!(words_per_minute(&TranscriptionResult::default()), 0.0);
+        let result = TranscriptionResult { segments: vec![segment(1.0, 1.0, "hello")], ..TranscriptionResult::default() };
+        assert_eq!(words_per_minute(&result), 0.0);
+    }
+
+    #[test]
+    fn test_split_into_sentences() {
+        let segments = vec![
+            segment(0.0, 4.0, " Hi there. Dr. Smith is"),
+            segment(4.0, 6.0, " here! Bye"),
+        ];
+        let sentences = split_into_sentences(&segments);
+        let texts: Vec<&str> = sentences.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Hi there.", "Dr. Smith is here!", "Bye"]);
+
+        // " Hi there." spans characters 1..10 of a 23-character, 4-second segment.
+        assert!((sentences[0].start - 4.0 / 23.0).abs() < 1e-9);
+        assert!((sentences[0].end - 40.0 / 23.0).abs() < 1e-9);
+        assert!(sentences[1].start < 4.0 && sentences[1].end > 4.0);
+        assert!((sentences[2].end - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_split_into_sentences_keeps_lowest_confidence() {
+        let segments = vec![
+            Segment { avg_logprob: -0.2, no_speech_prob: Some(0.1), ..segment(0.0, 2.0, " One. Two") },
+            Segment { avg_logprob: -1.4, no_speech_prob: Some(0.3), ..segment(2.0, 4.0, " three. Four.") },
+        ];
+        let scores: Vec<(f32, Option<f32>)> = split_into_sentences(&segments).iter().map(|s| (s.avg_logprob, s.no_speech_prob)).collect();
+        assert_eq!(scores, vec![(-0.2, Some(0.1)), (-1.4, Some(0.3)), (-1.4, Some(0.3))]);
+
+        let unscored = split_into_sentences(&[segment(0.0, 2.0, " One. Two.")]);
+        assert!(unscored.iter().all(|s| s.no_speech_prob.is_none()));
+    }
+
+    #[test]
+    fn test_segment_is_confident() {
+        let scored = |avg_logprob: f32, no_speech_prob: f32| {
+            Segment { avg_logprob, no_speech_prob: Some(no_speech_prob), ..segment(0.0, 1.0, " Hello.") }
+        };
+        assert!(scored(-0.3, 0.1).is_confident(0.6));
+        assert!(scored(-1.0, 0.6).is_confident(0.6));
+        // Hallucinated text over silence: likely no speech.
+        assert!(!scored(-0.3, 0.61).is_confident(0.6));
+        // Text the decoder was unsure of.
+        assert!(!scored(-1.01, 0.0).is_confident(0.6));
+        assert!(!scored(-0.3, 0.2).is_confident(0.1));
+        // No no-speech estimate: only the log probability counts.
+        let unscored = |avg_logprob: f32| Segment { avg_logprob, ..segment(0.0, 1.0, " Hello.") };
+        assert!(unscored(-0.3).is_confident(0.0));
+        assert!(!unscored(-1.01).is_confident(0.6));
+    }
+
+    #[test]
+    fn test_split_into_sentences_keeps_initials_and_lowercase_continuations() {
+        let sentences = split_into_sentences(&[segment(0.0, 1.0, "J. R. R. Tolkien wrote it, e.g. the Hobbit. The end.")]);
+        let texts: Vec<&str> = sentences.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["J. R. R. Tolkien wrote it, e.g. the Hobbit.", "The end."]);
+        assert!(split_into_sentences(&[segment(0.0, 1.0, "   ")]).is_empty());
+    }
+
+    #[test]
+    fn test_write_timestamped_text() {
+        let segments = vec![
+            segment(83.4, 85.0, " Hello there."),
+            segment(85.0, 86.0, "   "),
+            segment(3723.9, 3725.0, " Past the hour."),
+        ];
+
+        let mut out = Vec::new();
+        write_timestamped_text(&segments, &mut out, TimestampFormat::Clock).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[00:01:23] Hello there.\n[01:02:03] Past the hour.\n");
+
+        let mut out = Vec::new();
+        write_timestamped_text(&segments, &mut out, TimestampFormat::Seconds).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[83.40] Hello there.\n[3723.90] Past the hour.\n");
+    }
+
+    #[test]
+    fn test_to_srt() {
+        let segments = vec![
+            segment(0.25, 0.9, " Hi."),
+            segment(0.9, 1.0, "  "),
+            segment(3723.4567, 3725.0, " Past the hour. "),
+        ];
+        let expected = "\
+1
+00:00:00,250 --> 00:00:00,900
+Hi.
+
+2
+01:02:03,457 --> 01:02:05,000
+Past the hour.
+
+";
+        assert_eq!(to_srt(&segments), expected);
+        assert_eq!(to_srt(&[]), "");
+    }
+
+    #[test]
+    fn test_to_srt_clamps_inverted_times() {
+        assert_eq!(to_srt(&[segment(2.0, 1.5, "x")]), "1\n00:00:02,000 --> 00:00:02,000\nx\n\n");
+    }
+
+    #[test]
+    fn test_to_vtt_matches_fixture() {
+        let segments = vec![
+            segment(0.25, 0.9, " Hi."),
+            segment(0.5, 2.0, " Overlapping start."),
+            segment(2.0, 2.5, ""),
+            segment(3723.4567, 3725.0, " Past the hour."),
+        ];
+        assert_eq!(to_vtt(&segments), include_str!("../tests/fixtures/sample.vtt"));
+        assert_eq!(to_vtt(&[]), "WEBVTT\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json_round_trips() {
+        let segments = vec![segment(0.0, 1.5, "Hello."), segment(1.5, 3.0, "World.")];
+        let json = to_json(&segments, "base.en", Some("en")).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["model"], "base.en");
+        assert_eq!(value["language"], "en");
+        assert_eq!(value["segments"][1]["start"], 1.5);
+        assert_eq!(value["segments"][1]["avg_logprob"], 0.0);
+        assert!(value["segments"][1]["no_speech_prob"].is_null());
+        let parsed: Vec<Segment> = serde_json::from_value(value["segments"].clone()).unwrap();
+        assert_eq!(parsed, segments);
+
+        let value: serde_json::Value = serde_json::from_str(&to_json(&[], "tiny", None).unwrap()).unwrap();
+        assert!(value["language"].is_null());
+    }
+
+    #[test]
+    fn test_merge_tokens_into_words() {
+        let token = |text: &str, start: f64, end: f64| TokenTiming { text: text.to_string(), start, end, special: false };
+        let special = |text: &str, at: f64| TokenTiming { text: text.to_string(), start: at, end: at, special: true };
+        let tokens = vec![
+            special("[_BEG_]", 0.0),
+            token(" Hello", 0.0, 0.4),
+            token(",", 0.4, 0.45),
+            token(" un", 0.6, 0.8),
+            token("believ", 0.8, 1.1),
+            token("able", 1.1, 1.3),
+            token(" day", 1.4, 1.7),
+            token(".", 1.7, 1.75),
+            special("[_TT_88]", 1.76),
+        ];
+        let words = merge_tokens_into_words(&tokens);
+        let texts: Vec<&str> = words.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(texts, vec!["Hello,", "unbelievable", "day."]);
+        assert_eq!((words[1].start, words[1].end), (0.6, 1.3));
+        assert_eq!((words[2].start, words[2].end), (1.4, 1.75));
+        assert!(words.windows(2).all(|w| w[0].end <= w[1].start));
+        assert!(merge_tokens_into_words(&[special("[_BEG_]", 0.0)]).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! On-disk cache of transcription results, keyed by the audio and decoding parameters.
+//!
+//! Enabled with the `serde` feature and switched on at runtime with
+//! [`set_transcription_cache_enabled`]. Results are stored as JSON under `transcriptions/`
+//! in the model cache directory. Whisper decodes deterministically for identical input,
+//! model and parameters, so a hit returns exactly what a fresh run would.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use log::warn;
+use sha2::{Digest, Sha256};
+use whisper_rs::WhisperContext;
+use crate::error::WhisperStreamError;
+use crate::transcribe::DecodingParams;
+use crate::transcript::TranscriptionResult;
+
+/// Name of the cache subdirectory holding cached transcription results.
+const TRANSCRIPTIONS_DIR_NAME: &str = "transcriptions";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns the transcription cache on or off for the whole process. It is off by default.
+///
+/// While enabled, [`crate::transcribe`] returns a stored result when the same samples were
+/// already transcribed with the same model and [`DecodingParams`]. Only calls that set
+/// [`TranscribeOptions::model_id`](crate::TranscribeOptions::model_id) are cached.
+pub fn set_transcription_cache_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether the transcription cache is enabled.
+pub fn transcription_cache_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Deletes all cached transcription results.
+pub fn clear_transcription_cache() -> Result<(), WhisperStreamError> {
+    let dir = transcriptions_dir()?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(WhisperStreamError::from)?;
+    }
+    Ok(())
+}
+
+fn transcriptions_dir() -> Result<PathBuf, WhisperStreamError> {
+    Ok(crate::model::cache_dir()?.join(TRANSCRIPTIONS_DIR_NAME))
+}
+
+/// Hashes everything that determines a transcription: the model, the parameters and the samples.
+///
+/// `model_id` identifies the model file (see [`crate::model_identity`]); the model's type,
+/// vocabulary size and quantization alone can't tell a fine-tuned model from the stock one,
+/// or a model from its updated version.
+pub(crate) fn cache_key(ctx: &WhisperContext, model_id: &str, samples: &[f32], params: &DecodingParams) -> Result<String, WhisperStreamError> {
+    key_for([ctx.model_type(), ctx.model_n_vocab(), ctx.model_ftype()], model_id, samples, params)
+}
+
+fn key_for(model_hparams: [i32; 3], model_id: &str, samples: &[f32], params: &DecodingParams) -> Result<String, WhisperStreamError> {
+    let mut hasher = Sha256::new();
+    hasher.update((model_id.len() as u64).to_le_bytes());
+    hasher.update(model_id.as_bytes());
+    for value in model_hparams {
+        hasher.update(value.to_le_bytes());
+    }
+    hasher.update(params.to_json()?.as_bytes());
+    for sample in samples {
+        hasher.update(sample.to_le_bytes());
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Returns the cached result for `key`, if there is one.
+pub(crate) fn load(key: &str) -> Option<TranscriptionResult> {
+    load_from(&transcriptions_dir().ok()?, key)
+}
+
+/// Stores `result` under `key`. Failures are logged, not returned: the cache is best effort.
+pub(crate) fn store(key: &str, result: &TranscriptionResult) {
+    if let Err(e) = transcriptions_dir().and_then(|dir| store_in(&dir, key, result)) {
+        warn!("Failed to cache transcription result: {}", e);
+    }
+}
+
+fn load_from(dir: &Path, key: &str) -> Option<TranscriptionResult> {
+    let json = fs::read_to_string(dir.join(format!("{}.json", key))).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn store_in(dir: &Path, key: &str, result: &TranscriptionResult) -> Result<(), WhisperStreamError> {
+    fs::create_dir_all(dir).map_err(WhisperStreamError::from)?;
+    let json = serde_json::to_string(result)
+        .map_err(|e| WhisperStreamError::Internal(format!("Failed to serialize transcription result: {}", e)))?;
+    fs::write(dir.join(format!("{}.json", key)), json).map_err(WhisperStreamError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Segment;
+
+    #[test]
+    fn test_cache_key_depends_on_model_identity() {
+        let params = DecodingParams::default();
+        let samples = [0.0, 0.5, -0.5];
+        // A stock model and a fine-tune of the same size share type, vocabulary and ftype.
+        let hparams = [1, 51864, 1];
+        let stock = key_for(hparams, "sha256:aaaa", &samples, &params).unwrap();
+        assert_eq!(stock, key_for(hparams, "sha256:aaaa", &samples, &params).unwrap());
+        assert_ne!(stock, key_for(hparams, "sha256:bbbb", &samples, &params).unwrap());
+        assert_ne!(stock, key_for(hparams, "file:/models/fine-tuned.bin:1000:0", &samples, &params).unwrap());
+    }
+
+    #[test]
+    fn test_store_and_load_round_trip() {
+        let dir = std::env::temp_dir().join("whisper-stream-rs-test-transcription-cache");
+        let result = TranscriptionResult {
+            segments: vec![Segment { start: 0.0, end: 1.5, text: " Hello.".to_string(), avg_logprob: -0.25, no_speech_prob: None }],
+            language: Some("en".to_string()),
+        };
+        store_in(&dir, "abc", &result).unwrap();
+        assert_eq!(load_from(&dir, "abc"), Some(result));
+        assert_eq!(load_from(&dir, "missing"), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,14 @@
-use std::sync::mpsc::{self, Receiver};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
+use crate::audio_utils::{Agc, BufferPool, Vad, WavAudioRecorder};
+use crate::cancel::CancellationToken;
+use crate::error::WhisperStreamError;
 use crate::model::Model;
+use crate::transcribe::DecodingParams;
 
 /// Events emitted by the transcription stream.
 ///
@@ -44,8 +52,9 @@ pub enum Event {
 ///     }
 /// }
 /// ```
+#[derive(Clone)]
 pub struct WhisperStream {
-    // Will own the background thread and config
+    shutdown_signal: Arc<AtomicBool>,
 }
 
 /// Builder for [`WhisperStream`].
@@ -66,14 +75,24 @@ pub struct WhisperStreamBuilder {
     device: Option<String>,
     language: Option<String>,
     record_to_wav: Option<String>,
+    record_speech_to_wav: Option<String>,
     step_ms: u32,
     length_ms: u32,
     keep_ms: u32,
     max_tokens: i32,
     n_threads: i32,
     compute_partials: bool,
+    emit_partial_every_ms: Option<u32>,
+    auto_gain_control: bool,
+    discard_initial_ms: u32,
     logging_enabled: bool,
     model: Option<Model>,
+    model_path: Option<PathBuf>,
+    fallback_models: Vec<Model>,
+    partial_model: Option<Model>,
+    cancellation_token: Option<CancellationToken>,
+    adaptive_step_ms: Option<(u32, u32)>,
+    target_real_time_factor: f32,
 }
 
 impl WhisperStreamBuilder {
@@ -89,6 +108,16 @@ impl WhisperStreamBuilder {
         self.record_to_wav = Some(path.to_string());
         self
     }
+    /// Additionally records only the audio the VAD ([`crate::Vad`]) classifies as speech to `path`.
+    ///
+    /// Works alongside or without [`WhisperStreamBuilder::record_to_wav`]. Decisions are made per
+    /// `step_ms` chunk, so kept chunks may carry a little leading or trailing silence. Because
+    /// silent chunks are dropped, this recording is shorter than the full one and its timeline
+    /// does not line up with it or with transcript timestamps.
+    pub fn record_speech_to_wav(mut self, path: &str) -> Self {
+        self.record_speech_to_wav = Some(path.to_string());
+        self
+    }
     pub fn step_ms(mut self, ms: u32) -> Self {
         self.step_ms = ms;
         self
@@ -113,6 +142,22 @@ impl WhisperStreamBuilder {
         self.compute_partials = enabled;
         self
     }
+    /// Re-decodes the buffered audio for a `ProvisionalLiveUpdate` only once every `ms` of new audio,
+    /// instead of after every `step_ms` chunk. The window is still finalized as soon as it is full.
+    pub fn emit_partial_every_ms(mut self, ms: u32) -> Self {
+        self.emit_partial_every_ms = Some(ms);
+        self
+    }
+    /// Applies adaptive gain control ([`crate::Agc`]) to captured audio before it is recorded or transcribed.
+    pub fn auto_gain_control(mut self, enabled: bool) -> Self {
+        self.auto_gain_control = enabled;
+        self
+    }
+    /// Discards the first `ms` of microphone audio so device warm-up isn't recorded or transcribed.
+    pub fn discard_initial_ms(mut self, ms: u32) -> Self {
+        self.discard_initial_ms = ms;
+        self
+    }
     pub fn disable_logging(mut self) -> Self {
         self.logging_enabled = false;
         self
@@ -121,6 +166,81 @@ impl WhisperStreamBuilder {
         self.model = Some(model);
         self
     }
+    /// Loads the model from a local file instead of downloading one of the built-in [`Model`]s.
+    ///
+    /// No network access is needed, which suits custom fine-tuned models and air-gapped
+    /// machines. Takes precedence over [`WhisperStreamBuilder::model`] and the fallback chain.
+    pub fn model_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.model_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+    /// Smaller models to fall back to, in order, if the main model can't be loaded for lack of memory.
+    ///
+    /// For example `.model(Model::SmallEn).fallback_models(&[Model::BaseEn, Model::TinyEn])`.
+    /// A downgrade is reported as a `SystemMessage`; if every model fails, the stream sends
+    /// `Error(WhisperStreamError::OutOfMemory)` and ends.
+    pub fn fallback_models(mut self, models: &[Model]) -> Self {
+        self.fallback_models = models.to_vec();
+        self
+    }
+    /// Decodes `ProvisionalLiveUpdate`s with a smaller, faster model (e.g. [`Model::TinyEn`]),
+    /// while the main model still produces every `SegmentTranscript`.
+    ///
+    /// Both models are fetched with `ensure_model` and kept loaded for the lifetime of the stream.
+    /// If the partial model can't be fetched or loaded, a `SystemMessage` warns about it and
+    /// the main model decodes the partials too.
+    pub fn partial_model(mut self, model: Model) -> Self {
+        self.partial_model = Some(model);
+        self
+    }
+    /// Lets the stream tune how often it decodes, between every `min_ms` and every `max_ms`
+    /// of new audio.
+    ///
+    /// Each decode's time is divided by the duration of the audio added since the previous
+    /// decode. Whisper encodes a fixed 30s context, so a decode costs about the same however
+    /// much audio is new: above [`WhisperStreamBuilder::target_real_time_factor`] decodes are
+    /// spaced further apart to keep up, and well below it they run more often for lower
+    /// latency. Decodes can't run more often than `step_ms` chunks arrive. The starting
+    /// interval is `emit_partial_every_ms`, or `step_ms` if that isn't set. Finalizing a full
+    /// `length_ms` window is never delayed.
+    pub fn adaptive_step_ms(mut self, min_ms: u32, max_ms: u32) -> Self {
+        self.adaptive_step_ms = Some((min_ms.min(max_ms), max_ms.max(min_ms)));
+        self
+    }
+    /// Real-time factor that adaptive decode spacing aims to stay under (default `0.5`, i.e.
+    /// decoding takes at most half as long as the audio it adds). Must be positive and finite,
+    /// or [`WhisperStreamBuilder::build`] fails. Has no effect without `adaptive_step_ms`.
+    pub fn target_real_time_factor(mut self, rtf: f32) -> Self {
+        self.target_real_time_factor = rtf;
+        self
+    }
+    /// Aborts the stream when `token` is cancelled.
+    ///
+    /// Unlike [`WhisperStream::shutdown`], nothing is drained: an in-flight decode is aborted,
+    /// buffered audio is dropped, an `Error(WhisperStreamError::Cancelled)` event is sent and the
+    /// event channel closes. A recording, if any, is still finalized.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+    /// Returns the exact decoding parameters the stream will pass to Whisper for every window.
+    pub fn decoding_params(&self) -> DecodingParams {
+        DecodingParams {
+            n_threads: self.n_threads,
+            max_tokens: self.max_tokens,
+            language: self.language.clone(),
+            ..DecodingParams::default()
+        }
+    }
+    /// Rejects settings the stream can't run with.
+    fn validate(&self) -> Result<(), WhisperStreamError> {
+        if !(self.target_real_time_factor.is_finite() && self.target_real_time_factor > 0.0) {
+            return Err(WhisperStreamError::Transcription(format!(
+                "target_real_time_factor must be a positive, finite number (got {})", self.target_real_time_factor
+            )));
+        }
+        Ok(())
+    }
     pub fn build(self) -> Result<(WhisperStream, Receiver<Event>), crate::error::WhisperStreamError> {
         // Set up logging if enabled
         if self.logging_enabled {
@@ -128,53 +248,86 @@ impl WhisperStreamBuilder {
             whisper_rs::install_logging_hooks();
         }
 
+        self.validate()?;
         let (tx, rx) = mpsc::channel();
         let config = self;
-        let selected_model = config.model.unwrap_or(Model::BaseEn);
+        let selected_model = config.model.unwrap_or_else(crate::model::default_model);
+        let shutdown_signal = Arc::new(AtomicBool::new(false));
+        let thread_shutdown_signal = shutdown_signal.clone();
         thread::spawn(move || {
-            use crate::model::ensure_model;
+            use crate::model::{ensure_model, ensure_model_from_path, load_context, load_context_with_fallback};
             use crate::audio::{AudioInput};
-            use crate::audio_utils::{pad_audio_if_needed, WavAudioRecorder};
-            use whisper_rs::{WhisperContext, WhisperContextParameters, FullParams, SamplingStrategy};
-            use log::info;
-            use std::sync::Arc;
+            use crate::audio_utils::{pad_audio_into, MIN_WHISPER_SAMPLES};
+            use log::{debug, info, warn};
 
-            const MIN_WHISPER_SAMPLES: usize = 16800; // 1050ms at 16kHz (increased buffer)
+            let system_info = whisper_rs::print_system_info();
+            info!("Whisper System Info: \n{}", system_info);
 
-            let model_path = match ensure_model(selected_model) {
-                Ok(p) => p,
+            // A model at an explicit path is loaded as is; otherwise walk the fallback chain.
+            let model_chain: Vec<Model> = std::iter::once(selected_model).chain(config.fallback_models.iter().copied()).collect();
+            let loaded = match config.model_path.as_ref() {
+                Some(path) => ensure_model_from_path(path).and_then(|p| load_context(&p)).map(|ctx| (None, ctx)),
+                None => load_context_with_fallback(&model_chain).map(|(model, ctx)| (Some(model), ctx)),
+            };
+            let (selected_model, ctx) = match loaded {
+                Ok(loaded) => loaded,
                 Err(e) => {
                     let _ = tx.send(Event::Error(e));
                     return;
                 }
             };
+            if let Some(model) = selected_model
+                && model != model_chain[0]
+            {
+                let _ = tx.send(Event::SystemMessage(format!(
+                    "[Warning] Not enough memory for {}; using {} instead.", model_chain[0], model
+                )));
+            }
 
-            let system_info = whisper_rs::print_system_info();
-            info!("Whisper System Info: \n{}", system_info);
-
-            let ctx = match WhisperContext::new_with_params(
-                model_path.to_str().unwrap_or("invalid_model_path"),
-                WhisperContextParameters::default(),
-            ) {
-                Ok(c) => c,
-                Err(e) => {
-                    let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
-                    return;
+            // A distinct partial model gets its own context; otherwise partials use the main one.
+            let partial_ctx = match config.partial_model.filter(|m| Some(*m) != selected_model) {
+                Some(partial_model) => {
+                    match ensure_model(partial_model).and_then(|p| load_context(&p)) {
+                        Ok(c) => {
+                            info!("Using {} for partials and the main model for final segments.", partial_model);
+                            Some(c)
+                        }
+                        // Partials are only a latency optimisation, so the main model can do them.
+                        Err(e) => {
+                            warn!("Could not load partial model {}: {}", partial_model, e);
+                            let _ = tx.send(Event::SystemMessage(format!(
+                                "[Warning] Could not load {} for partials ({}); using the main model instead.", partial_model, e
+                            )));
+                            None
+                        }
+                    }
                 }
+                None => None,
             };
 
             let audio_input = match AudioInput::new(config.device.as_deref(), config.step_ms) {
-                Ok(input) => input,
+                Ok(input) => input.with_discard_initial_ms(config.discard_initial_ms),
                 Err(e) => {
                     let _ = tx.send(Event::Error(e));
                     return;
                 }
             };
             let audio_rx = audio_input.start_capture_16k();
+            let buffer_pool = audio_input.buffer_pool();
             let sample_rate = 16000;
             let n_samples_window = (sample_rate as f32 * (config.length_ms as f32 / 1000.0)) as usize;
             let n_samples_overlap = (sample_rate as f32 * (config.keep_ms as f32 / 1000.0)) as usize;
-            let mut segment_window: Vec<f32> = Vec::with_capacity(n_samples_window);
+            let to_samples = |ms: u32| (sample_rate as f32 * (ms as f32 / 1000.0)) as usize;
+            let mut n_samples_partial_interval = config.emit_partial_every_ms.map(to_samples);
+            let mut adaptive_step = config.adaptive_step_ms.map(|(min_ms, max_ms)| {
+                let initial = n_samples_partial_interval.unwrap_or_else(|| to_samples(config.step_ms));
+                AdaptiveStep::new(to_samples(min_ms), to_samples(max_ms), initial, config.target_real_time_factor)
+            });
+            if let Some(adaptive) = adaptive_step.as_ref() {
+                n_samples_partial_interval = Some(adaptive.len());
+            }
+            // Reused for padding short windows up to Whisper's minimum length.
+            let mut padding_scratch: Vec<f32> = Vec::with_capacity(MIN_WHISPER_SAMPLES);
             let mut state = match ctx.create_state() {
                 Ok(s) => s,
                 Err(e) => {
@@ -183,19 +336,23 @@ impl WhisperStreamBuilder {
                 }
             };
 
-            let mut params_full = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-            params_full.set_n_threads(config.n_threads);
-            params_full.set_max_tokens(config.max_tokens);
-            params_full.set_print_special(false);
-            params_full.set_print_progress(false);
-            params_full.set_print_realtime(false);
-            params_full.set_print_timestamps(false);
-            if let Some(ref lang) = config.language {
-                params_full.set_language(Some(lang));
+            let mut partial_state = match partial_ctx.as_ref().map(|c| c.create_state()).transpose() {
+                Ok(s) => s,
+                Err(e) => {
+                    let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
+                    return;
+                }
+            };
+
+            let decoding_params = config.decoding_params();
+            let mut params_full = decoding_params.full_params();
+            if let Some(token) = config.cancellation_token.as_ref() {
+                token.attach(&mut params_full);
             }
             let arc_params_full = Arc::new(params_full);
+            let is_cancelled = || config.cancellation_token.as_ref().is_some_and(CancellationToken::is_cancelled);
 
-            let mut wav_audio_recorder = match WavAudioRecorder::new(config.record_to_wav.as_deref()) {
+            let wav_audio_recorder = match WavAudioRecorder::new(config.record_to_wav.as_deref()) {
                 Ok(recorder) => recorder,
                 Err(e) => {
                     let _ = tx.send(Event::Error(e));
@@ -206,46 +363,93 @@ impl WhisperStreamBuilder {
                 }
             };
 
-            if wav_audio_recorder.is_recording() {
-                if let Some(path_str) = config.record_to_wav.as_ref() {
-                    info!("[Recording] Saving transcribed audio to {}...", path_str);
-                    let _ = tx.send(Event::SystemMessage(format!("[Recording] Saving transcribed audio to {}...", path_str)));
-                }
+            if wav_audio_recorder.is_recording()
+                && let Some(path_str) = config.record_to_wav.as_ref()
+            {
+                info!("[Recording] Saving transcribed audio to {}...", path_str);
+                let _ = tx.send(Event::SystemMessage(format!("[Recording] Saving transcribed audio to {}...", path_str)));
             }
 
-            for pcmf32_new_result in audio_rx {
-                let pcmf32_new = match pcmf32_new_result {
-                    Ok(audio_data) => {
-                        if audio_data.is_empty() {
-                            continue;
-                        }
-                        audio_data
-                    }
-                    Err(audio_err) => {
-                        let _ = tx.send(Event::Error(audio_err));
-                        continue;
+            let speech_recorder = match WavAudioRecorder::new(config.record_speech_to_wav.as_deref()) {
+                Ok(recorder) => recorder,
+                Err(e) => {
+                    let _ = tx.send(Event::Error(e));
+                    match WavAudioRecorder::new(None) {
+                        Ok(no_op_recorder) => no_op_recorder,
+                        Err(_) => return,
                     }
+                }
+            };
+            // 30ms frames with 300ms of hangover so word endings aren't cut.
+            let speech_vad = speech_recorder.is_recording().then(|| Vad::new(480, 0.01, 10));
+
+            if speech_recorder.is_recording()
+                && let Some(path_str) = config.record_speech_to_wav.as_ref()
+            {
+                info!("[Recording] Saving speech-only audio to {}...", path_str);
+                let _ = tx.send(Event::SystemMessage(format!("[Recording] Saving speech-only audio to {}...", path_str)));
+            }
+
+            let mut pipeline = ChunkPipeline {
+                agc: config.auto_gain_control.then(|| Agc::new(0.1, 10.0)),
+                recorder: wav_audio_recorder,
+                speech_recorder,
+                speech_vad,
+                window: Vec::with_capacity(n_samples_window),
+                samples_since_partial: 0,
+                samples_since_final: 0,
+            };
+
+            loop {
+                if is_cancelled() {
+                    break;
+                }
+                if thread_shutdown_signal.load(Ordering::Relaxed) {
+                    info!("Shutdown requested; draining buffered audio.");
+                    break;
+                }
+                // Poll so a shutdown request is noticed even while no audio arrives.
+                let chunk = match audio_rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(chunk) => chunk,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                if !pipeline.ingest(chunk, &buffer_pool, &tx) {
+                    continue;
+                }
+                if pipeline.window.len() < n_samples_window
+                    && n_samples_partial_interval.is_some_and(|interval| pipeline.samples_since_partial < interval)
+                {
+                    continue;
+                }
+                let new_samples = std::mem::take(&mut pipeline.samples_since_partial);
+                let audio_for_processing = pad_audio_into(&pipeline.window, MIN_WHISPER_SAMPLES, &mut padding_scratch);
+                let active_state = match (pipeline.window.len() >= n_samples_window, partial_state.as_mut()) {
+                    (false, Some(partial)) => partial,
+                    _ => &mut state,
                 };
 
-                if wav_audio_recorder.is_recording() {
-                    if let Err(e) = wav_audio_recorder.write_audio_chunk(&pcmf32_new) {
-                        let _ = tx.send(Event::Error(e));
+                let decode_started = Instant::now();
+                let full_result = active_state.full(arc_params_full.as_ref().clone(), audio_for_processing);
+                if let Some(adaptive) = adaptive_step.as_mut() {
+                    let rtf = decode_started.elapsed().as_secs_f32() / (new_samples as f32 / sample_rate as f32);
+                    if adaptive.update(rtf) {
+                        n_samples_partial_interval = Some(adaptive.len());
+                        debug!("Real-time factor {:.2}; decoding every {}ms now.", rtf, adaptive.len() * 1000 / sample_rate);
                     }
                 }
-
-                segment_window.extend_from_slice(&pcmf32_new);
-                let audio_for_processing = pad_audio_if_needed(&segment_window, MIN_WHISPER_SAMPLES);
-
-                if let Err(e) = state.full(arc_params_full.as_ref().clone(), &audio_for_processing) {
-                    let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
+                if let Err(e) = full_result {
+                    if !is_cancelled() {
+                        let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
+                    }
                     continue;
                 }
 
                 let mut current_text = String::new();
-                match state.full_n_segments() {
+                match active_state.full_n_segments() {
                     Ok(num_segments) => {
                         for i in 0..num_segments {
-                            match state.full_get_segment_text(i) {
+                            match active_state.full_get_segment_text(i) {
                                 Ok(seg) => current_text.push_str(&seg),
                                 Err(e) => {
                                     let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
@@ -262,25 +466,41 @@ impl WhisperStreamBuilder {
 
                 if !current_text.trim().is_empty() {
                     let is_low_quality = crate::score::is_low_quality_output(&current_text);
-                    if segment_window.len() >= n_samples_window {
+                    if pipeline.window.len() >= n_samples_window {
                         let _ = tx.send(Event::SegmentTranscript { text: current_text.clone(), is_low_quality });
                     } else if config.compute_partials {
                         let _ = tx.send(Event::ProvisionalLiveUpdate { text: current_text.clone(), is_low_quality });
                     }
                 }
 
-                if segment_window.len() >= n_samples_window {
-                    if n_samples_overlap > 0 && segment_window.len() > n_samples_overlap {
-                        segment_window = segment_window[segment_window.len() - n_samples_overlap..].to_vec();
+                if pipeline.window.len() >= n_samples_window {
+                    pipeline.samples_since_final = 0;
+                    if n_samples_overlap > 0 && pipeline.window.len() > n_samples_overlap {
+                        // Shift the overlap to the front in place, keeping the allocation.
+                        pipeline.window.drain(..pipeline.window.len() - n_samples_overlap);
                     } else {
-                        segment_window.clear();
+                        pipeline.window.clear();
                     }
                 }
             }
 
-            if !segment_window.is_empty() {
-                let final_audio_for_processing = pad_audio_if_needed(&segment_window, MIN_WHISPER_SAMPLES);
-                if let Err(e) = state.full(arc_params_full.as_ref().clone(), &final_audio_for_processing) {
+            // Chunks captured while the last decode ran are still queued; they belong to the
+            // final transcript and the recordings.
+            if !is_cancelled() {
+                for chunk in audio_rx.try_iter() {
+                    pipeline.ingest(chunk, &buffer_pool, &tx);
+                }
+            }
+            // Stop ingesting: dropping the receiver makes the capture thread stop on its next send.
+            drop(audio_rx);
+
+            if is_cancelled() {
+                info!("Stream cancelled; discarding buffered audio.");
+                let _ = tx.send(Event::Error(crate::error::WhisperStreamError::Cancelled));
+            } else if pipeline.samples_since_final > 0 && !pipeline.window.is_empty() {
+                // Only the kept overlap remains after a finalized window; don't transcribe it twice.
+                let final_audio_for_processing = pad_audio_into(&pipeline.window, MIN_WHISPER_SAMPLES, &mut padding_scratch);
+                if let Err(e) = state.full(arc_params_full.as_ref().clone(), final_audio_for_processing) {
                     let _ = tx.send(Event::Error(crate::error::WhisperStreamError::from(e)));
                 } else {
                     let mut final_text = String::new();
@@ -307,18 +527,20 @@ impl WhisperStreamBuilder {
                 }
             }
 
-            match wav_audio_recorder.finalize() {
-                Ok(Some(msg)) => {
-                    info!("{}", msg);
-                    let _ = tx.send(Event::SystemMessage(msg));
-                }
-                Ok(None) => { /* No recording was active, nothing to report */ }
-                Err(e) => {
-                    let _ = tx.send(Event::Error(e));
+            for recorder in [pipeline.recorder, pipeline.speech_recorder] {
+                match recorder.finalize() {
+                    Ok(Some(msg)) => {
+                        info!("{}", msg);
+                        let _ = tx.send(Event::SystemMessage(msg));
+                    }
+                    Ok(None) => { /* No recording was active, nothing to report */ }
+                    Err(e) => {
+                        let _ = tx.send(Event::Error(e));
+                    }
                 }
             }
         });
-        Ok((WhisperStream {}, rx))
+        Ok((WhisperStream { shutdown_signal }, rx))
     }
 }
 
@@ -328,18 +550,28 @@ impl WhisperStream {
             device: None,
             language: Some("en".to_string()),
             record_to_wav: None,
+            record_speech_to_wav: None,
             step_ms: 800,
             length_ms: 5000,
             keep_ms: 200,
             max_tokens: 32,
             n_threads: std::thread::available_parallelism().map(|n| n.get() as i32).unwrap_or(8),
             compute_partials: true,
+            emit_partial_every_ms: None,
+            auto_gain_control: false,
+            discard_initial_ms: 0,
             logging_enabled: true,
             model: None,
+            model_path: None,
+            fallback_models: Vec::new(),
+            partial_model: None,
+            cancellation_token: None,
+            adaptive_step_ms: None,
+            target_real_time_factor: 0.5,
         }
     }
     pub fn list_devices() -> Result<Vec<String>, crate::error::WhisperStreamError> {
-        crate::audio::AudioInput::available_input_devices()
+        crate::audio::list_input_devices()
     }
     pub fn list_models() -> Vec<Model> {
         Model::list()
@@ -348,8 +580,184 @@ impl WhisperStream {
         // Will start the background thread in next phase
         Ok(())
     }
+    /// Stops the stream gracefully.
+    ///
+    /// Microphone capture stops, audio that is buffered but not yet finalized is transcribed
+    /// one last time and sent as a final `SegmentTranscript`, the recording (if any) is
+    /// finalized, and then the event channel closes. Returns immediately; keep reading the
+    /// receiver until it ends to get the remaining events. Calling it more than once is harmless.
+    pub fn shutdown(&self) {
+        self.shutdown_signal.store(true, Ordering::Relaxed);
+    }
+    /// Returns `true` once [`WhisperStream::shutdown`] has been requested.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown_signal.load(Ordering::Relaxed)
+    }
+    /// Same as [`WhisperStream::shutdown`].
     pub fn stop(&mut self) -> Result<(), crate::error::WhisperStreamError> {
-        // Will stop the background thread in next phase
+        self.shutdown();
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// Per-chunk handling shared by the capture loop and the drain at shutdown: gain control,
+/// both recordings, and appending to the decode window.
+struct ChunkPipeline {
+    agc: Option<Agc>,
+    recorder: WavAudioRecorder,
+    speech_recorder: WavAudioRecorder,
+    speech_vad: Option<Vad>,
+    /// Audio of the current decode window.
+    window: Vec<f32>,
+    samples_since_partial: usize,
+    /// Samples in `window` that have not been part of a finalized window yet.
+    samples_since_final: usize,
+}
+
+impl ChunkPipeline {
+    /// Processes one captured chunk and returns whether it added audio to the window. Capture
+    /// errors are forwarded to `tx` and empty chunks skipped.
+    fn ingest(&mut self, chunk: Result<Vec<f32>, WhisperStreamError>, buffer_pool: &BufferPool, tx: &Sender<Event>) -> bool {
+        let mut chunk = match chunk {
+            Ok(chunk) if chunk.is_empty() => return false,
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = tx.send(Event::Error(e));
+                return false;
+            }
+        };
+
+        if let Some(agc) = self.agc.as_mut() {
+            agc.process(&mut chunk);
+        }
+
+        if self.recorder.is_recording()
+            && let Err(e) = self.recorder.write_audio_chunk(&chunk)
+        {
+            let _ = tx.send(Event::Error(e));
+        }
+
+        if let Some(vad) = self.speech_vad.as_mut() {
+            let decisions = vad.process(&chunk);
+            let is_speech = if decisions.is_empty() { vad.is_speaking() } else { decisions.contains(&true) };
+            if is_speech
+                && let Err(e) = self.speech_recorder.write_audio_chunk(&chunk)
+            {
+                let _ = tx.send(Event::Error(e));
+            }
+        }
+
+        self.window.extend_from_slice(&chunk);
+        self.samples_since_partial += chunk.len();
+        self.samples_since_final += chunk.len();
+        buffer_pool.give(chunk);
+        true
+    }
+}
+
+/// Decode interval controller for [`WhisperStreamBuilder::adaptive_step_ms`].
+struct AdaptiveStep {
+    min_len: usize,
+    max_len: usize,
+    len: usize,
+    target_rtf: f32,
+}
+
+impl AdaptiveStep {
+    /// Relative change applied per adjustment.
+    const STEP: f32 = 1.25;
+
+    fn new(min_len: usize, max_len: usize, initial_len: usize, target_rtf: f32) -> Self {
+        Self { min_len, max_len, len: initial_len.clamp(min_len, max_len), target_rtf }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Adjusts the interval for the measured real-time factor; returns whether it changed.
+    ///
+    /// A slow decode spaces decodes further apart; only a fast one below half the target
+    /// brings them closer, so the interval doesn't oscillate. Non-finite measurements, e.g.
+    /// from a decode that added no audio, are ignored.
+    fn update(&mut self, rtf: f32) -> bool {
+        if !rtf.is_finite() {
+            return false;
+        }
+        let new_len = if rtf > self.target_rtf {
+            ((self.len as f32 * Self::STEP) as usize).min(self.max_len)
+        } else if rtf < self.target_rtf / 2.0 {
+            ((self.len as f32 / Self::STEP) as usize).max(self.min_len)
+        } else {
+            self.len
+        };
+        let changed = new_len != self.len;
+        self.len = new_len;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_pipeline_takes_queued_chunks_into_window_and_recording() {
+        let path = std::env::temp_dir().join("whisper-stream-rs-test-drain.wav");
+        let mut pipeline = ChunkPipeline {
+            agc: None,
+            recorder: WavAudioRecorder::new(path.to_str()).unwrap(),
+            speech_recorder: WavAudioRecorder::new(None).unwrap(),
+            speech_vad: None,
+            window: Vec::new(),
+            samples_since_partial: 0,
+            samples_since_final: 0,
+        };
+        let (audio_tx, audio_rx) = mpsc::channel();
+        let (tx, rx) = mpsc::channel();
+        // Chunks that piled up while the last decode ran, as seen at shutdown.
+        audio_tx.send(Ok(vec![0.25; 800])).unwrap();
+        audio_tx.send(Ok(Vec::new())).unwrap();
+        audio_tx.send(Err(WhisperStreamError::AudioDevice("device hiccup".to_string()))).unwrap();
+        audio_tx.send(Ok(vec![-0.25; 800])).unwrap();
+
+        let added = audio_rx.try_iter().map(|chunk| pipeline.ingest(chunk, &BufferPool::new(4), &tx)).collect::<Vec<_>>();
+        assert_eq!(added, vec![true, false, false, true]);
+        assert_eq!(pipeline.window.len(), 1600);
+        assert_eq!(pipeline.samples_since_final, 1600);
+        assert!(matches!(rx.try_recv(), Ok(Event::Error(WhisperStreamError::AudioDevice(_)))));
+
+        pipeline.recorder.finalize().unwrap();
+        assert_eq!(hound::WavReader::open(&path).unwrap().len(), 1600);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_adaptive_step_stays_within_bounds() {
+        let mut step = AdaptiveStep::new(16000, 80000, 100000, 0.5);
+        assert_eq!(step.len(), 80000);
+
+        assert!(step.update(0.1));
+        assert_eq!(step.len(), 64000);
+        assert!(!step.update(0.4));
+        assert_eq!(step.len(), 64000);
+
+        for _ in 0..20 {
+            step.update(0.9);
+        }
+        assert_eq!(step.len(), 80000);
+        for _ in 0..20 {
+            step.update(0.0);
+        }
+        assert_eq!(step.len(), 16000);
+        assert!(!step.update(f32::INFINITY));
+        assert!(!step.update(f32::NAN));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_target_real_time_factor() {
+        for rtf in [0.0, -0.5, f32::NAN, f32::INFINITY] {
+            let builder = WhisperStream::builder().adaptive_step_ms(200, 2000).target_real_time_factor(rtf);
+            assert!(matches!(builder.validate(), Err(WhisperStreamError::Transcription(_))), "{}", rtf);
+        }
+    }
+}